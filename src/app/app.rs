@@ -1,21 +1,21 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     Frame,
 };
 use strum::{EnumCount, EnumIter};
-use supermusr_common::Time;
+use supermusr_common::Channel;
 
 use crate::{
     app::{Display, Results, Setup},
-    finder::MessageFinder,
-    graphics::{Bound, Bounds, FileFormat, GraphSaver},
-    messages::Cache,
+    finder::{MessageFinder, SearchStatus},
+    graphics::{self, Bounds, GraphSaver, RenderOptions},
+    messages::{Cache, DigitiserMetadata, DigitiserTrace, Pipelines, Trace, UnitConversion},
     tui::{
-        Component, ComponentContainer, FocusableComponent, InputComponent, Statusbar, TextBox,
-        TuiComponent,
+        Component, ComponentContainer, FocusableComponent, GraphData, InputComponent, Statusbar,
+        TextBox, TuiComponent,
     },
-    Select,
+    DetectorSettings, IntensityScale, Select, Units, UserBounds,
 };
 
 pub(crate) trait AppDependencies {
@@ -45,6 +45,8 @@ pub(crate) struct App<D: AppDependencies> {
     results: TuiComponent<Results>,
     display: TuiComponent<Display>,
     help: TuiComponent<TextBox<String>>,
+    /// The conversion of graphs into physical units.
+    units: Units,
 }
 
 impl<'a, D: AppDependencies> App<D> {
@@ -53,18 +55,32 @@ impl<'a, D: AppDependencies> App<D> {
     /// # Attributes
     /// - message_finder: TODO
     /// - select: TODO
-    pub(crate) fn new(message_finder: D::MessageFinder, select: &Select) -> Self {
+    /// - bounds: the graph bounds given on the command line.
+    /// - units: the conversion of graphs into physical units given on the command line.
+    /// - scale: the scale of the intensity axis of graphs given on the command line.
+    /// - processing: the pipelines graphed alongside each trace given on the command line.
+    /// - detector: the settings of the local event detector given on the command line.
+    pub(crate) fn new(
+        message_finder: D::MessageFinder,
+        select: &Select,
+        bounds: &UserBounds,
+        units: &Units,
+        scale: &IntensityScale,
+        processing: &Pipelines,
+        detector: &DetectorSettings,
+    ) -> Self {
         let mut app = App {
             quit: false,
             is_changed: true,
             cache: None,
             message_finder,
             focus: Default::default(),
-            setup: Setup::new(select),
+            setup: Setup::new(select, bounds, processing, detector),
             status: Statusbar::new(select),
             results: Results::new(),
-            display: Display::new(),
+            display: Display::new(scale),
             help: TextBox::new(Default::default(), None),
+            units: units.clone(),
         };
         app.focused_component_mut().set_focus(true);
         app
@@ -98,9 +114,40 @@ impl<'a, D: AppDependencies> App<D> {
         }
         // If a result is available, pop it from the [MessageFinder].
         if let Some(cache) = self.message_finder.results() {
+            // Remember the current selection, so it can be kept in the new results.
+            let selected = self
+                .cache
+                .as_ref()
+                .and_then(|old_cache| self.results.select(old_cache))
+                .map(|(metadata, _, channel)| (metadata.clone(), channel));
+
             self.results.new_cache(&cache.cache);
             self.status.set_info(&cache);
 
+            if let Some((metadata, channel)) = selected {
+                let selection = if self.display.is_following() {
+                    self.results
+                        .select_latest(&cache.cache, metadata.id, channel)
+                } else {
+                    self.results
+                        .select_metadata(&cache.cache, &metadata, channel)
+                };
+                // Graphing the trace resets the view, so it is only graphed again if it has changed,
+                // and then the zoom and viewport are kept.
+                if let Some((metadata, trace)) = selection {
+                    if let Some(data) = self.graph_data(metadata, trace, channel) {
+                        if !self.display.is_selected(metadata, data.events.as_ref()) {
+                            self.display.select(
+                                metadata,
+                                data,
+                                &self.setup.get_detector_settings(),
+                                true,
+                            );
+                        }
+                    }
+                }
+            }
+
             // Take ownership of the cache
             self.cache = Some(cache.cache);
 
@@ -112,6 +159,117 @@ impl<'a, D: AppDependencies> App<D> {
             self.results.update(cache);
         }
     }
+
+    /// Returns the given channel of the trace, and its event list, to be graphed as set in [Setup],
+    /// or [None] if the trace does not have the channel.
+    fn graph_data(
+        &self,
+        metadata: &DigitiserMetadata,
+        trace: &DigitiserTrace,
+        channel: Channel,
+    ) -> Option<GraphData> {
+        Some(GraphData {
+            trace: trace.traces.get(&channel)?.clone(),
+            events: trace
+                .events
+                .as_ref()
+                .and_then(|events| events.get(&channel))
+                .cloned(),
+            user_bounds: self.setup.get_user_bounds(),
+            conversion: self.units.conversion(metadata.id, trace),
+            processing: self.setup.get_processing(),
+        })
+    }
+
+    /// Returns the options with which graphs are saved, as set in [Setup] and [Display].
+    ///
+    /// # Attributes
+    /// - bounds: the bounds of the graph, in sample indices and raw intensities.
+    /// - conversion: the conversion of the graph into physical units, if any.
+    fn render_options(&self, bounds: Bounds, conversion: Option<UnitConversion>) -> RenderOptions {
+        RenderOptions {
+            layout: self.setup.get_layout(),
+            size: self.setup.get_image_size(),
+            bounds,
+            conversion,
+            scale: self.display.get_scale().clone(),
+            processing: self.setup.get_processing(),
+        }
+    }
+
+    /// Reports a failure to save in the status bar, rather than ending the app.
+    fn report_save(&mut self, saved: anyhow::Result<()>) {
+        if let Err(e) = saved {
+            self.status
+                .set_status(SearchStatus::Text(format!("Save failed: {e}")));
+        }
+    }
+
+    /// Saves every channel of the selected digitiser's trace to one file.
+    fn save_selected_channels(&self) -> anyhow::Result<()> {
+        if let Some(cache) = &self.cache {
+            if let Some((metadata, trace, _)) = self.results.select(cache) {
+                let channels = trace.channels();
+                let traces: Vec<&Trace> = channels.iter().map(|c| &trace.traces[c]).collect();
+                let conversion = self.units.conversion(metadata.id, trace);
+                let bounds = Bounds::from_traces(&traces)
+                    .include_processed(&traces, &self.setup.get_processing(), conversion.as_ref())
+                    .amend_with_user_bounds(&self.setup.get_user_bounds());
+                graphics::save_channels::<D::GraphSaver>(
+                    self.setup.get_format(),
+                    &self.setup.get_path(),
+                    metadata,
+                    trace,
+                    channels,
+                    &self.render_options(bounds, conversion),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the selected channel of the selected trace.
+    ///
+    /// CSV and JSON files instead hold every channel of the selected trace.
+    /// # Attributes
+    /// - zoomed: if true, the graph is bounded to the view in [Display], otherwise to the whole trace.
+    fn save_selected(&self, zoomed: bool) -> anyhow::Result<()> {
+        if let Some(cache) = &self.cache {
+            if let Some((metadata, trace, channel)) = self.results.select(cache) {
+                let Some(samples) = trace.traces.get(&channel) else {
+                    anyhow::bail!("Channel {channel} is not in the selected trace");
+                };
+                let conversion = self.units.conversion(metadata.id, trace);
+                let bounds = zoomed
+                    .then(|| self.display.get_zoomed_bounds())
+                    .flatten()
+                    .unwrap_or_else(|| {
+                        Bounds::from_trace(samples)
+                            .include_processed(
+                                &[samples],
+                                &self.setup.get_processing(),
+                                conversion.as_ref(),
+                            )
+                            .amend_with_user_bounds(&self.setup.get_user_bounds())
+                    });
+                let format = self.setup.get_format();
+                let channels = if format.is_data() {
+                    trace.channels()
+                } else {
+                    vec![channel]
+                };
+                graphics::save_channels::<D::GraphSaver>(
+                    format,
+                    &self.setup.get_path(),
+                    metadata,
+                    trace,
+                    channels,
+                    &self.render_options(bounds, conversion),
+                )?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<D: AppDependencies> ComponentContainer for App<D> {
@@ -140,7 +298,7 @@ impl<D: AppDependencies> Component for App<D> {
             let chunk = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(8),
+                    Constraint::Length(11),
                     Constraint::Length(5),
                     Constraint::Min(8),
                     Constraint::Length(3),
@@ -168,11 +326,40 @@ impl<D: AppDependencies> Component for App<D> {
 impl<D: AppDependencies> InputComponent for App<D> {
     fn handle_key_press(&mut self, key: KeyEvent) {
         if key.code == KeyCode::Esc {
-            self.quit = true;
+            // Halt the search in progress, or quit if there is none.
+            if !self.message_finder.halt_search() {
+                self.quit = true;
+            }
         } else if key == KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT) {
             self.set_focus_index(self.focus.clone() as isize - 1);
         } else if key == KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE) {
             self.set_focus_index(self.focus.clone() as isize + 1)
+        } else if matches!(self.focus, Focus::Display) && key.code == KeyCode::Char('w') {
+            let saved = self.save_selected(false);
+            self.report_save(saved);
+        } else if matches!(self.focus, Focus::Display) && key.code == KeyCode::Char('v') {
+            // Zoom the display to the bounds typed into the setup.
+            self.display
+                .zoom_to_user_bounds(&self.setup.get_user_bounds());
+        } else if matches!(self.focus, Focus::Display) && key.code == KeyCode::Char('a') {
+            // Save every trace in the cache.
+            let saved = self.cache.as_ref().map(|cache| {
+                graphics::save_cache::<D::GraphSaver>(
+                    self.setup.get_format(),
+                    &self.setup.get_path(),
+                    cache,
+                    &self.render_options(Bounds::default(), None),
+                    &self.setup.get_user_bounds(),
+                    &self.units,
+                )
+            });
+            if let Some(saved) = saved {
+                self.report_save(saved.map(|_| ()));
+            }
+        } else if matches!(self.focus, Focus::Display) && key.code == KeyCode::Char('c') {
+            // Save every channel of the selected digitiser's trace to one file.
+            let saved = self.save_selected_channels();
+            self.report_save(saved);
         } else if key.code == KeyCode::Enter {
             match self.focus {
                 Focus::Setup => {
@@ -180,41 +367,22 @@ impl<D: AppDependencies> InputComponent for App<D> {
                 }
                 Focus::Results => {
                     if let Some(cache) = &self.cache {
-                        if let Some((_, trace, channel)) = self.results.select(cache) {
-                            self.display.select(
-                                trace.traces.get(&channel).expect(""),
-                                trace
-                                    .events
-                                    .as_ref()
-                                    .and_then(|events| events.get(&channel)),
-                            );
+                        if let Some((metadata, trace, channel)) = self.results.select(cache) {
+                            // A channel missing from the trace is not graphed.
+                            if let Some(data) = self.graph_data(metadata, trace, channel) {
+                                self.display.select(
+                                    metadata,
+                                    data,
+                                    &self.setup.get_detector_settings(),
+                                    false,
+                                );
+                            }
                         }
                     }
                 }
                 Focus::Display => {
-                    if let Some(cache) = &self.cache {
-                        if let Some((metadata, trace, channel)) = self.results.select(cache) {
-                            D::GraphSaver::save_as_svg(
-                                trace,
-                                vec![channel],
-                                FileFormat::Svg
-                                    .build_path(&self.setup.get_path(), metadata, channel)
-                                    .expect(""),
-                                self.setup.get_image_size(),
-                                Bounds {
-                                    time: Bound::from(
-                                        1.0,
-                                        [0, trace.traces[&channel].len() as Time].into_iter(),
-                                    ),
-                                    intensity: Bound::from(
-                                        1.0,
-                                        trace.traces[&channel].iter().copied(),
-                                    ),
-                                },
-                            )
-                            .expect("");
-                        }
-                    }
+                    let saved = self.save_selected(true);
+                    self.report_save(saved);
                 }
             }
         } else {
@@ -222,4 +390,13 @@ impl<D: AppDependencies> InputComponent for App<D> {
         }
         self.is_changed = true;
     }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        // Movement without a button pressed does nothing, so need not cause a redraw.
+        if mouse.kind == MouseEventKind::Moved {
+            return;
+        }
+        self.route_mouse_event(mouse);
+        self.is_changed = true;
+    }
 }
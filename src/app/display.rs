@@ -1,35 +1,162 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     Frame,
 };
 
 use crate::{
-    messages::{EventList, Trace},
+    cli_structs::{DetectorSettings, IntensityScale, UserBounds},
+    graphics::Bounds,
+    messages::{DigitiserMetadata, EventComparison, EventList},
     tui::{
-        ComponentStyle, FocusableComponent, Graph, GraphProperties, InputComponent,
-        ParentalFocusComponent, TextBox, TuiComponent, TuiComponentBuilder,
+        ComponentStyle, FocusableComponent, Graph, GraphData, GraphProperties, InputComponent,
+        ParentalFocusComponent, TextBox, TuiComponent, TuiComponentBuilder, ZoomAxes,
     },
     Component,
 };
 
+/// The selected trace, kept so that it can be graphed again when the scale changes.
+struct Selection {
+    /// The metadata of the trace.
+    metadata: DigitiserMetadata,
+    data: GraphData,
+    /// The events detected locally in the trace, if a detector is set.
+    detected: Option<EventList>,
+}
+
 pub(crate) struct Display {
     info: TuiComponent<TextBox<String>>,
     graph: TuiComponent<Graph>,
+    /// The frame metadata of the selected trace.
+    frame_info: String,
+    /// The number of events detected locally in the selected trace, and how they compare with those of the broker.
+    detection_info: String,
+    /// Flag specifying whether the display should follow the latest trace of the selected digitiser and channel.
+    follow: bool,
+    /// The selected trace, if any.
+    selection: Option<Selection>,
+    /// The scale of the intensity axis.
+    scale: IntensityScale,
 }
 
 impl Display {
-    pub(crate) fn new() -> TuiComponent<Self> {
+    /// Creates a new Display instance.
+    ///
+    /// # Attributes
+    /// - scale: the scale of the intensity axis given on the command line.
+    pub(crate) fn new(scale: &IntensityScale) -> TuiComponent<Self> {
         TuiComponentBuilder::new(ComponentStyle::selectable()).build(Self {
             info: TextBox::new(Default::default(), None),
             graph: Graph::new(),
+            frame_info: Default::default(),
+            detection_info: Default::default(),
+            follow: false,
+            selection: None,
+            scale: scale.clone(),
         })
     }
 
-    pub(crate) fn select(&mut self, trace_data: &Trace, event_data: Option<&EventList>) {
-        self.graph.set(trace_data, event_data);
+    /// Returns the scale of the intensity axis, as toggled by the user.
+    pub(crate) fn get_scale(&self) -> &IntensityScale {
+        &self.scale
+    }
+
+    /// Returns whether the display should follow the latest trace of the selected digitiser and channel.
+    pub(crate) fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// Returns true if the trace with the given metadata is selected, with the same number of events.
+    ///
+    /// # Attributes
+    /// - metadata: the metadata of the trace.
+    /// - events: the event list of the trace, if present.
+    pub(crate) fn is_selected(
+        &self,
+        metadata: &DigitiserMetadata,
+        events: Option<&EventList>,
+    ) -> bool {
+        self.selection.as_ref().is_some_and(|selection| {
+            selection.metadata == *metadata
+                && selection.data.events.as_ref().map(Vec::len) == events.map(Vec::len)
+        })
+    }
+
+    /// Graphs the trace, and detects events in it if a detector is set.
+    ///
+    /// # Attributes
+    /// - metadata: the metadata of the trace.
+    /// - data: the trace and event list to graph, and how to graph them.
+    /// - detector: the settings of the local event detector.
+    /// - keep_view: if true, the zoom and viewport of the previous trace are kept, otherwise the whole trace is in view.
+    pub(crate) fn select(
+        &mut self,
+        metadata: &DigitiserMetadata,
+        data: GraphData,
+        detector: &DetectorSettings,
+        keep_view: bool,
+    ) {
+        self.frame_info = format!("id: {}, {}", metadata.id, metadata.frame_info());
+        let detected = detector
+            .detector()
+            .map(|detector| detector.detect(&data.trace));
+        self.detection_info = match (&detected, &data.events) {
+            (Some(detected), Some(events)) => format!(
+                "detected: {}, {}",
+                detected.len(),
+                EventComparison::new(detected, events, detector.match_tolerance)
+            ),
+            (Some(detected), None) => format!("detected: {}", detected.len()),
+            (None, _) => String::new(),
+        };
+        self.selection = Some(Selection {
+            metadata: metadata.clone(),
+            data,
+            detected,
+        });
+        self.regraph(keep_view);
+    }
+
+    /// Graphs the selected trace again, with the current scale.
+    ///
+    /// # Attributes
+    /// - keep_view: if true, the zoom and viewport are kept, otherwise the whole trace is in view.
+    fn regraph(&mut self, keep_view: bool) {
+        if let Some(selection) = &self.selection {
+            self.graph.set(
+                &selection.data,
+                selection.detected.as_ref(),
+                &self.scale,
+                keep_view,
+            );
+        }
+        self.update_info();
+    }
+
+    /// Returns the bounding rectangle of the graph currently in view, in sample indices and raw intensities,
+    /// if a trace is selected.
+    pub(crate) fn get_zoomed_bounds(&self) -> Option<Bounds> {
+        self.graph.get_zoomed_bounds()
+    }
+
+    /// Zooms the graph to the ranges given in sample indices and raw intensities, where set.
+    pub(crate) fn zoom_to_user_bounds(&mut self, user_bounds: &UserBounds) {
+        self.graph.zoom_to_user_bounds(user_bounds);
+        self.update_info();
+    }
+
+    /// Sets the info box to the frame metadata, the graph's properties, whether the display is following,
+    /// the readout of the graph's cursors, and the counts of locally detected events.
+    fn update_info(&mut self) {
         if let Some(properties) = self.graph.get_properties() {
-            self.info.set(format!("{}", properties.get_info()));
+            let follow = if self.follow { " [following]" } else { "" };
+            let cursor = self.graph.get_cursor_info().unwrap_or_default();
+            self.info.set(format!(
+                "{}\n{}{follow}\n{cursor}\n{}",
+                self.frame_info,
+                properties.get_info(),
+                self.detection_info
+            ));
         }
     }
 
@@ -49,7 +176,7 @@ impl Component for Display {
         let (info, results) = {
             let chunk = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Length(4), Constraint::Min(16)])
+                .constraints([Constraint::Length(6), Constraint::Min(16)])
                 .split(area);
             (chunk[0], chunk[1])
         };
@@ -61,11 +188,56 @@ impl Component for Display {
 
 impl InputComponent for Display {
     fn handle_key_press(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('f') {
+            self.follow = !self.follow;
+        } else if key.code == KeyCode::Char('[') {
+            self.graph.step_cursor(-1);
+        } else if key.code == KeyCode::Char(']') {
+            self.graph.step_cursor(1);
+        } else if key.code == KeyCode::Char('{') {
+            self.graph.step_cursor_to_event(false);
+        } else if key.code == KeyCode::Char('}') {
+            self.graph.step_cursor_to_event(true);
+        } else if key.code == KeyCode::Char('m') {
+            self.graph.toggle_second_cursor();
+        } else if key.code == KeyCode::Char('x') {
+            self.graph.clear_cursors();
+        } else if key.code == KeyCode::Char('=') {
+            self.graph.zoom_to_fit();
+        } else if key.code == KeyCode::Char('l') {
+            self.scale.log_intensity = !self.scale.log_intensity;
+            self.regraph(false);
+        } else if key.code == KeyCode::Char('i') {
+            self.scale.invert_intensity = !self.scale.invert_intensity;
+            self.regraph(false);
+        } else if key.code == KeyCode::Char('b') {
+            self.scale.subtract_baseline = !self.scale.subtract_baseline;
+            self.regraph(false);
+        }
         if let Some(properties) = self.graph.get_properties_mut() {
             if key.code == KeyCode::Char('+') {
-                properties.zoom_in();
+                properties.zoom_in(ZoomAxes::Both);
             } else if key.code == KeyCode::Char('-') {
-                properties.zoom_out();
+                properties.zoom_out(ZoomAxes::Both);
+            } else if key.modifiers.contains(KeyModifiers::CONTROL) {
+                // Control and the arrow keys zoom the axes independently.
+                if key.code == KeyCode::Up {
+                    properties.zoom_in(ZoomAxes::Intensity);
+                } else if key.code == KeyCode::Down {
+                    properties.zoom_out(ZoomAxes::Intensity);
+                } else if key.code == KeyCode::Right {
+                    properties.zoom_in(ZoomAxes::Time);
+                } else if key.code == KeyCode::Left {
+                    properties.zoom_out(ZoomAxes::Time);
+                }
+            } else if key.code == KeyCode::PageUp {
+                properties.page(-1.0);
+            } else if key.code == KeyCode::PageDown {
+                properties.page(1.0);
+            } else if key.code == KeyCode::Home {
+                properties.scroll_to_start();
+            } else if key.code == KeyCode::End {
+                properties.scroll_to_end();
             } else if key.code == KeyCode::Up {
                 properties.move_viewport(0.0, 1.0);
             } else if key.code == KeyCode::Down {
@@ -75,8 +247,13 @@ impl InputComponent for Display {
             } else if key.code == KeyCode::Right {
                 properties.move_viewport(1.0, 0.0);
             }
-            self.info.set(format!("{}", properties.get_info()))
         }
+        self.update_info();
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        self.graph.handle_mouse_event(mouse);
+        self.update_info();
     }
 }
 
@@ -1,9 +1,9 @@
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     Frame,
 };
-use supermusr_common::Channel;
+use supermusr_common::{Channel, DigitizerId};
 
 use crate::{
     messages::{Cache, DigitiserMetadata, DigitiserTrace},
@@ -14,9 +14,45 @@ use crate::{
     Component,
 };
 
+/// An entry of the cache, as listed in a row of [Results].
+enum Row {
+    /// The trace with the given metadata.
+    Trace(DigitiserMetadata),
+    /// The event list with the given metadata, which has no trace.
+    Events(DigitiserMetadata),
+}
+
+impl Row {
+    fn metadata(&self) -> &DigitiserMetadata {
+        match self {
+            Row::Trace(metadata) | Row::Events(metadata) => metadata,
+        }
+    }
+
+    /// Returns the channels of the entry in the cache.
+    fn channels(&self, cache: &Cache) -> Vec<Channel> {
+        match self {
+            Row::Trace(metadata) => cache
+                .get_trace(metadata)
+                .map(|(_, trace)| trace.channels())
+                .unwrap_or_default(),
+            Row::Events(metadata) => {
+                let mut channels: Vec<Channel> = cache
+                    .get_events(metadata)
+                    .map(|events| events.keys().copied().collect())
+                    .unwrap_or_default();
+                channels.sort();
+                channels
+            }
+        }
+    }
+}
+
 pub(crate) struct Results {
     list: TuiComponent<ListBox<String>>,
     channels: TuiComponent<Channels>,
+    /// The entry of the cache listed in each row of [Self::list].
+    rows: Vec<Row>,
 }
 
 impl Results {
@@ -24,38 +60,131 @@ impl Results {
         TuiComponentBuilder::new(ComponentStyle::selectable()).build(Self {
             list: ListBox::new(&vec![], Some("Traces"), None),
             channels: Channels::new(),
+            rows: Vec::new(),
         })
     }
 
+    /// Lists the traces of the cache, and any event lists which have no trace, in order of their metadata.
     pub(crate) fn new_cache(&mut self, cache: &Cache) {
-        let list = cache
+        self.rows = cache
             .iter_traces()
-            .map(|(metadata, trace)| {
-                format!(
-                    "[{}]\nid: {}, num channels {}, num_bins: {}",
+            .map(|(metadata, _)| Row::Trace(metadata.clone()))
+            .chain(
+                cache
+                    .iter_unpaired_events()
+                    .map(|(metadata, _)| Row::Events(metadata.clone())),
+            )
+            .collect();
+        self.rows.sort_by(|a, b| a.metadata().cmp(b.metadata()));
+
+        let list = self
+            .rows
+            .iter()
+            .map(|row| match row {
+                Row::Trace(metadata) => {
+                    let (_, trace) = cache.get_trace(metadata).expect("");
+                    format!(
+                        "[{}]\nid: {}, num channels {}, num_bins: {}{}\n{}\n{}",
+                        metadata.timestamp,
+                        metadata.id,
+                        trace.traces.len(),
+                        trace
+                            .traces
+                            .iter()
+                            .map(|(_, t)| t.len())
+                            .max()
+                            .unwrap_or_default(),
+                        if trace.events.is_none() {
+                            ", no events"
+                        } else {
+                            ""
+                        },
+                        metadata.frame_info(),
+                        trace
+                            .source
+                            .map(|source| source.to_string())
+                            .unwrap_or_default()
+                    )
+                }
+                Row::Events(metadata) => format!(
+                    "[{}]\nid: {}, num channels {}, events with no trace\n{}",
                     metadata.timestamp,
                     metadata.id,
-                    trace.traces.len(),
-                    trace
-                        .traces
-                        .iter()
-                        .map(|(_, t)| t.len())
-                        .max()
-                        .unwrap_or_default()
-                )
+                    cache
+                        .get_events(metadata)
+                        .map(|events| events.len())
+                        .unwrap_or_default(),
+                    metadata.frame_info(),
+                ),
             })
             .collect();
         self.list.set(list);
     }
 
+    /// Returns the selected trace and channel, if a trace, rather than an event list with no trace, is selected.
     pub(crate) fn select<'a>(
         &mut self,
         cache: &'a Cache,
     ) -> Option<(&'a DigitiserMetadata, &'a DigitiserTrace, Channel)> {
-        self.list
-            .get_index()
-            .and_then(|i| cache.iter_traces().nth(i))
-            .and_then(|(m, t)| self.channels.get().map(|c| (m, t, c)))
+        let Row::Trace(metadata) = self.rows.get(self.list.get_index()?)? else {
+            return None;
+        };
+        let (metadata, trace) = cache.get_trace(metadata)?;
+        self.channels.get().map(|c| (metadata, trace, c))
+    }
+
+    /// Selects the entry of the cache with the given metadata, and the given channel, if present.
+    ///
+    /// Returns the selected metadata and trace, if present.
+    pub(crate) fn select_metadata<'a>(
+        &mut self,
+        cache: &'a Cache,
+        metadata: &DigitiserMetadata,
+        channel: Channel,
+    ) -> Option<(&'a DigitiserMetadata, &'a DigitiserTrace)> {
+        let index = self
+            .rows
+            .iter()
+            .position(|row| matches!(row, Row::Trace(m) if m == metadata))?;
+        self.select_index(cache, index, channel)
+    }
+
+    /// Selects the latest entry of the cache from the given digitiser, which contains the given channel.
+    ///
+    /// Returns the selected metadata and trace, if present.
+    pub(crate) fn select_latest<'a>(
+        &mut self,
+        cache: &'a Cache,
+        digitiser_id: DigitizerId,
+        channel: Channel,
+    ) -> Option<(&'a DigitiserMetadata, &'a DigitiserTrace)> {
+        let index = self.rows.iter().rposition(|row| match row {
+            Row::Trace(m) => {
+                m.id == digitiser_id
+                    && cache
+                        .get_trace(m)
+                        .is_some_and(|(_, t)| t.traces.contains_key(&channel))
+            }
+            Row::Events(_) => false,
+        })?;
+        self.select_index(cache, index, channel)
+    }
+
+    /// Selects the trace of the cache listed in the given row, and the given channel.
+    fn select_index<'a>(
+        &mut self,
+        cache: &'a Cache,
+        index: usize,
+        channel: Channel,
+    ) -> Option<(&'a DigitiserMetadata, &'a DigitiserTrace)> {
+        let Row::Trace(metadata) = self.rows.get(index)? else {
+            return None;
+        };
+        let (metadata, trace) = cache.get_trace(metadata)?;
+        self.list.set_index(Some(index));
+        self.channels.set(trace.channels());
+        self.channels.select(channel);
+        Some((metadata, trace))
     }
 
     ///
@@ -64,8 +193,8 @@ impl Results {
             let channels = self
                 .list
                 .get_index()
-                .and_then(|i| cache.iter_traces().nth(i))
-                .map(|(_, trace)| trace.traces.keys().copied().collect::<Vec<_>>())
+                .and_then(|i| self.rows.get(i))
+                .map(|row| row.channels(cache))
                 .unwrap_or_default();
             self.channels.set(channels);
         }
@@ -93,6 +222,14 @@ impl InputComponent for Results {
         self.list.handle_key_press(key);
         self.channels.handle_key_press(key);
     }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        if self.list.is_under_mouse(&mouse) {
+            self.list.handle_mouse_event(mouse);
+        } else if self.channels.is_under_mouse(&mouse) {
+            self.channels.handle_mouse_event(mouse);
+        }
+    }
 }
 
 impl FocusableComponent for Results {
@@ -1,17 +1,19 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, MouseEvent};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect, Spacing},
     Frame,
 };
 use strum::{EnumCount, EnumIter, IntoEnumIterator};
-use supermusr_common::{Channel, DigitizerId};
+use supermusr_common::{Channel, DigitizerId, Intensity, Time};
 
 use crate::{
+    cli_structs::{DetectorSettings, MetadataFilter, Optional, UserBounds, ValueList},
     finder::{MessageFinder, SearchMode, SearchTarget},
-    graphics::FileFormat,
+    graphics::{FileFormat, GraphLayout},
+    messages::{Pipelines, Polarity},
     tui::{
         ComponentContainer, ComponentStyle, EditBox, FocusableComponent, InputComponent, ListBox,
         ParentalFocusComponent, TuiComponent, TuiComponentBuilder,
@@ -26,38 +28,80 @@ pub(crate) enum Focus {
     Date,
     Time,
     Number,
-    Channel,
-    DigitiserId,
+    Channels,
+    DigitiserIds,
+    FrameNumbers,
+    Period,
     NumPasses,
     MinStepSize,
     StepSizeMul,
+    Timeout,
+    MaxMessages,
     SavePath,
     Format,
+    Layout,
     Width,
     Height,
+    TimeMin,
+    TimeMax,
+    IntensityMin,
+    IntensityMax,
+    Processing,
+    DetectorThreshold,
+    DetectorPolarity,
+    DetectorBaseline,
+    DetectorDuration,
+    DetectorCoolOff,
+    MatchTolerance,
 }
 
 pub(crate) struct Setup {
     focus: Focus,
+    /// The metadata filter given on the command line.
+    filter: MetadataFilter,
     search_mode: TuiComponent<ListBox<SearchMode>>,
     date: TuiComponent<EditBox<NaiveDate>>,
     time: TuiComponent<EditBox<NaiveTime>>,
     number: TuiComponent<EditBox<usize>>,
-    channel: TuiComponent<EditBox<Channel>>,
-    digitiser_id: TuiComponent<EditBox<DigitizerId>>,
+    channels: TuiComponent<EditBox<ValueList<Channel>>>,
+    digitiser_ids: TuiComponent<EditBox<ValueList<DigitizerId>>>,
+    frame_numbers: TuiComponent<EditBox<ValueList<u32>>>,
+    period: TuiComponent<EditBox<Optional<u64>>>,
     num_passes: TuiComponent<EditBox<u32>>,
     min_step_size: TuiComponent<EditBox<i64>>,
     step_size_mul: TuiComponent<EditBox<i64>>,
+    timeout: TuiComponent<EditBox<u64>>,
+    max_messages: TuiComponent<EditBox<usize>>,
     save_path: TuiComponent<EditBox<String>>,
     format: TuiComponent<EditBox<FileFormat>>,
+    layout: TuiComponent<EditBox<GraphLayout>>,
     width: TuiComponent<EditBox<u32>>,
     height: TuiComponent<EditBox<u32>>,
+    time_min: TuiComponent<EditBox<Optional<Time>>>,
+    time_max: TuiComponent<EditBox<Optional<Time>>>,
+    intensity_min: TuiComponent<EditBox<Optional<Intensity>>>,
+    intensity_max: TuiComponent<EditBox<Optional<Intensity>>>,
+    /// The pipelines whose results are graphed alongside each trace.
+    processing: TuiComponent<EditBox<Pipelines>>,
+    /// The settings of the local event detector.
+    detector_threshold: TuiComponent<EditBox<Optional<Intensity>>>,
+    detector_polarity: TuiComponent<EditBox<Polarity>>,
+    detector_baseline: TuiComponent<EditBox<Intensity>>,
+    detector_duration: TuiComponent<EditBox<usize>>,
+    detector_cool_off: TuiComponent<EditBox<usize>>,
+    match_tolerance: TuiComponent<EditBox<Time>>,
 }
 
 impl Setup {
-    pub(crate) fn new(select: &Select) -> TuiComponent<Self> {
+    pub(crate) fn new(
+        select: &Select,
+        bounds: &UserBounds,
+        processing: &Pipelines,
+        detector: &DetectorSettings,
+    ) -> TuiComponent<Self> {
         let comp = Self {
             focus: Default::default(),
+            filter: select.filter.clone(),
             search_mode: ListBox::new(
                 &SearchMode::iter().collect::<Vec<_>>(),
                 Some("Search Mode"),
@@ -66,15 +110,40 @@ impl Setup {
             date: EditBox::new(select.timestamp.date_naive(), Some("Date (YYYY-MM-DD)")),
             time: EditBox::new(select.timestamp.time(), Some("Time (hh:mm:ss.f)")),
             number: EditBox::new(1, Some("Number to Collect")),
-            channel: EditBox::new(1, Some("Channel to Seek")),
-            digitiser_id: EditBox::new(4, Some("Digitiser Id to Seek")),
+            channels: EditBox::new(select.channels.clone(), Some("Channels to Seek")),
+            digitiser_ids: EditBox::new(
+                select.digitiser_ids.clone(),
+                Some("Digitiser Ids to Seek"),
+            ),
+            frame_numbers: EditBox::new(select.frame_numbers.clone(), Some("Frame Numbers")),
+            period: EditBox::new(Optional(select.filter.period_number), Some("Period")),
             num_passes: EditBox::new(select.step.num_step_passes, Some("Num Step Passes")),
             min_step_size: EditBox::new(select.step.min_step_size, Some("Min Step Size")),
             step_size_mul: EditBox::new(select.step.step_mul_coef, Some("Step Size Mul Coef")),
+            timeout: EditBox::new(select.limits.search_timeout, Some("Timeout (s)")),
+            max_messages: EditBox::new(
+                select.limits.max_messages_scanned,
+                Some("Max Messages Scanned"),
+            ),
             save_path: EditBox::new("out".to_owned(), Some("Save Path")),
-            format: EditBox::new(FileFormat::Svg, Some("Image Format")),
+            format: EditBox::new(FileFormat::Svg, Some("File Format")),
+            layout: EditBox::new(GraphLayout::Overlay, Some("Layout")),
             width: EditBox::new(800, Some("Image Width")),
             height: EditBox::new(600, Some("Image Height")),
+            time_min: EditBox::new(Optional(bounds.time_min), Some("Time Min")),
+            time_max: EditBox::new(Optional(bounds.time_max), Some("Time Max")),
+            intensity_min: EditBox::new(Optional(bounds.intensity_min), Some("Intensity Min")),
+            intensity_max: EditBox::new(Optional(bounds.intensity_max), Some("Intensity Max")),
+            processing: EditBox::new(processing.clone(), Some("Processing (e.g. ma:8>diff)")),
+            detector_threshold: EditBox::new(
+                Optional(detector.detector_threshold),
+                Some("Detector Threshold"),
+            ),
+            detector_polarity: EditBox::new(detector.detector_polarity, Some("Polarity")),
+            detector_baseline: EditBox::new(detector.detector_baseline, Some("Baseline")),
+            detector_duration: EditBox::new(detector.detector_duration, Some("Duration")),
+            detector_cool_off: EditBox::new(detector.detector_cool_off, Some("Cool-off")),
+            match_tolerance: EditBox::new(detector.match_tolerance, Some("Match Tolerance")),
         };
         let mut setup = TuiComponentBuilder::new(ComponentStyle::default()).build(comp);
         setup.focused_component_mut().set_focus(true);
@@ -91,15 +160,30 @@ impl Setup {
             )
         };
         let number = *self.number.get();
-        let channel = *self.channel.get();
-        let digitiser_id = *self.digitiser_id.get();
+        let channels = self.channels.get().values().to_vec();
+        let digitiser_ids = self.digitiser_ids.get().values().to_vec();
+        let frame_numbers = self.frame_numbers.get().values().to_vec();
+        let timeout = Duration::from_secs(*self.timeout.get());
+        let max_messages_scanned = *self.max_messages.get();
         if let Some(mode) = self.search_mode.get_value() {
+            // The period box narrows a search by frame number, so it only applies in that mode.
+            let filter = match mode {
+                SearchMode::ByFrameNumbers => MetadataFilter {
+                    period_number: self.period.get().0,
+                    ..self.filter.clone()
+                },
+                _ => self.filter.clone(),
+            };
             message_finder.init_search(SearchTarget {
                 mode,
                 timestamp,
                 number,
-                channels: vec![channel],
-                digitiser_ids: vec![digitiser_id],
+                channels,
+                digitiser_ids,
+                frame_numbers,
+                timeout,
+                max_messages_scanned,
+                filter,
             });
         }
     }
@@ -108,9 +192,44 @@ impl Setup {
         PathBuf::from(self.save_path.get())
     }
 
+    pub(crate) fn get_format(&self) -> FileFormat {
+        *self.format.get()
+    }
+
+    pub(crate) fn get_layout(&self) -> GraphLayout {
+        *self.layout.get()
+    }
+
+    /// Returns the graph bounds, those which are unset are fitted to the data.
+    pub(crate) fn get_user_bounds(&self) -> UserBounds {
+        UserBounds {
+            time_min: self.time_min.get().0,
+            time_max: self.time_max.get().0,
+            intensity_min: self.intensity_min.get().0,
+            intensity_max: self.intensity_max.get().0,
+        }
+    }
+
     pub(crate) fn get_image_size(&self) -> (u32, u32) {
         (*self.width.get(), *self.height.get())
     }
+
+    /// Returns the pipelines whose results are graphed alongside each trace.
+    pub(crate) fn get_processing(&self) -> Pipelines {
+        self.processing.get().clone()
+    }
+
+    /// Returns the settings of the local event detector, which is disabled if the threshold is unset.
+    pub(crate) fn get_detector_settings(&self) -> DetectorSettings {
+        DetectorSettings {
+            detector_threshold: self.detector_threshold.get().0,
+            detector_polarity: *self.detector_polarity.get(),
+            detector_baseline: *self.detector_baseline.get(),
+            detector_duration: *self.detector_duration.get(),
+            detector_cool_off: *self.detector_cool_off.get(),
+            match_tolerance: *self.match_tolerance.get(),
+        }
+    }
 }
 
 impl Component for Setup {
@@ -127,13 +246,13 @@ impl Component for Setup {
 
         self.search_mode.render(frame, search_mode);
 
-        // Top/Bottom Division
-        let (top, bottom) = {
+        // Top/Bottom/Bounds Division
+        let (top, bottom, bounds) = {
             let chunk = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Ratio(1, 2); 2])
+                .constraints([Constraint::Ratio(1, 3); 3])
                 .split(area);
-            (chunk[0], chunk[1])
+            (chunk[0], chunk[1], chunk[2])
         };
 
         //
@@ -162,16 +281,18 @@ impl Component for Setup {
         self.time.render(frame, time);
 
         // Search Params Division
-        let (number, channel, digitiser_id) = {
+        let (number, channels, digitiser_ids, frame_numbers, period) = {
             let chunk = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints([Constraint::Ratio(1, 3); 3])
+                .constraints([Constraint::Ratio(1, 5); 5])
                 .split(search_params);
-            (chunk[0], chunk[1], chunk[2])
+            (chunk[0], chunk[1], chunk[2], chunk[3], chunk[4])
         };
         self.number.render(frame, number);
-        self.channel.render(frame, channel);
-        self.digitiser_id.render(frame, digitiser_id);
+        self.channels.render(frame, channels);
+        self.digitiser_ids.render(frame, digitiser_ids);
+        self.frame_numbers.render(frame, frame_numbers);
+        self.period.render(frame, period);
 
         //
         // Bottom Row
@@ -188,24 +309,26 @@ impl Component for Setup {
         };
 
         // Search Settings Division
-        let (num_passes, min_step_size, step_size_mul) = {
+        let (num_passes, min_step_size, step_size_mul, timeout, max_messages) = {
             let chunk = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints([Constraint::Ratio(1, 3); 3])
+                .constraints([Constraint::Ratio(1, 5); 5])
                 .split(search_settings);
-            (chunk[0], chunk[1], chunk[2])
+            (chunk[0], chunk[1], chunk[2], chunk[3], chunk[4])
         };
         self.num_passes.render(frame, num_passes);
         self.min_step_size.render(frame, min_step_size);
         self.step_size_mul.render(frame, step_size_mul);
+        self.timeout.render(frame, timeout);
+        self.max_messages.render(frame, max_messages);
 
         // Save Settings Division
-        let (save_path, format, width, height) = {
+        let (save_path, format, layout, width, height) = {
             let chunk = Layout::default()
                 .direction(Direction::Horizontal)
-                .constraints([Constraint::Ratio(1, 4); 4])
+                .constraints([Constraint::Ratio(1, 5); 5])
                 .split(save_settings);
-            (chunk[0], chunk[1], chunk[2], chunk[3])
+            (chunk[0], chunk[1], chunk[2], chunk[3], chunk[4])
         };
         self.num_passes.render(frame, num_passes);
         self.min_step_size.render(frame, min_step_size);
@@ -213,8 +336,62 @@ impl Component for Setup {
 
         self.save_path.render(frame, save_path);
         self.format.render(frame, format);
+        self.layout.render(frame, layout);
         self.width.render(frame, width);
         self.height.render(frame, height);
+
+        //
+        // Bounds Row
+        //
+
+        // Bounds/Analysis Division
+        let (graph_bounds, analysis) = {
+            let chunk = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, 2); 2])
+                .spacing(Spacing::Space(4))
+                .split(bounds);
+            (chunk[0], chunk[1])
+        };
+
+        // Graph Bounds Division
+        let (time_min, time_max, intensity_min, intensity_max) = {
+            let chunk = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, 4); 4])
+                .split(graph_bounds);
+            (chunk[0], chunk[1], chunk[2], chunk[3])
+        };
+        self.time_min.render(frame, time_min);
+        self.time_max.render(frame, time_max);
+        self.intensity_min.render(frame, intensity_min);
+        self.intensity_max.render(frame, intensity_max);
+
+        // Analysis Division
+        let (processing, threshold, polarity, baseline, duration, cool_off, tolerance) = {
+            let chunk = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Ratio(1, 4),
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(1, 8),
+                    Constraint::Ratio(1, 8),
+                ])
+                .split(analysis);
+            (
+                chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6],
+            )
+        };
+        self.processing.render(frame, processing);
+        self.detector_threshold.render(frame, threshold);
+        self.detector_polarity.render(frame, polarity);
+        self.detector_baseline.render(frame, baseline);
+        self.detector_duration.render(frame, duration);
+        self.detector_cool_off.render(frame, cool_off);
+        self.match_tolerance.render(frame, tolerance);
     }
 }
 
@@ -227,15 +404,31 @@ impl ComponentContainer for Setup {
             Focus::Date => &mut self.date,
             Focus::Time => &mut self.time,
             Focus::Number => &mut self.number,
-            Focus::Channel => &mut self.channel,
-            Focus::DigitiserId => &mut self.digitiser_id,
+            Focus::Channels => &mut self.channels,
+            Focus::DigitiserIds => &mut self.digitiser_ids,
+            Focus::FrameNumbers => &mut self.frame_numbers,
+            Focus::Period => &mut self.period,
             Focus::NumPasses => &mut self.num_passes,
             Focus::MinStepSize => &mut self.min_step_size,
             Focus::StepSizeMul => &mut self.step_size_mul,
+            Focus::Timeout => &mut self.timeout,
+            Focus::MaxMessages => &mut self.max_messages,
             Focus::SavePath => &mut self.save_path,
             Focus::Format => &mut self.format,
+            Focus::Layout => &mut self.layout,
             Focus::Width => &mut self.width,
             Focus::Height => &mut self.height,
+            Focus::TimeMin => &mut self.time_min,
+            Focus::TimeMax => &mut self.time_max,
+            Focus::IntensityMin => &mut self.intensity_min,
+            Focus::IntensityMax => &mut self.intensity_max,
+            Focus::Processing => &mut self.processing,
+            Focus::DetectorThreshold => &mut self.detector_threshold,
+            Focus::DetectorPolarity => &mut self.detector_polarity,
+            Focus::DetectorBaseline => &mut self.detector_baseline,
+            Focus::DetectorDuration => &mut self.detector_duration,
+            Focus::DetectorCoolOff => &mut self.detector_cool_off,
+            Focus::MatchTolerance => &mut self.match_tolerance,
         }
     }
 
@@ -258,6 +451,10 @@ impl InputComponent for Setup {
             self.focused_component_mut().handle_key_press(key);
         }
     }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        self.route_mouse_event(mouse);
+    }
 }
 
 impl FocusableComponent for Setup {
@@ -271,12 +468,28 @@ impl ParentalFocusComponent for Setup {
         self.date.propagate_parental_focus(focus);
         self.date.propagate_parental_focus(focus);
         self.number.propagate_parental_focus(focus);
-        self.channel.propagate_parental_focus(focus);
-        self.digitiser_id.propagate_parental_focus(focus);
+        self.channels.propagate_parental_focus(focus);
+        self.digitiser_ids.propagate_parental_focus(focus);
+        self.frame_numbers.propagate_parental_focus(focus);
+        self.period.propagate_parental_focus(focus);
         self.num_passes.propagate_parental_focus(focus);
         self.min_step_size.propagate_parental_focus(focus);
         self.step_size_mul.propagate_parental_focus(focus);
+        self.timeout.propagate_parental_focus(focus);
+        self.max_messages.propagate_parental_focus(focus);
         self.save_path.propagate_parental_focus(focus);
         self.format.propagate_parental_focus(focus);
+        self.layout.propagate_parental_focus(focus);
+        self.time_min.propagate_parental_focus(focus);
+        self.time_max.propagate_parental_focus(focus);
+        self.intensity_min.propagate_parental_focus(focus);
+        self.intensity_max.propagate_parental_focus(focus);
+        self.processing.propagate_parental_focus(focus);
+        self.detector_threshold.propagate_parental_focus(focus);
+        self.detector_polarity.propagate_parental_focus(focus);
+        self.detector_baseline.propagate_parental_focus(focus);
+        self.detector_duration.propagate_parental_focus(focus);
+        self.detector_cool_off.propagate_parental_focus(focus);
+        self.match_tolerance.propagate_parental_focus(focus);
     }
 }
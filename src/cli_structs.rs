@@ -1,7 +1,18 @@
 use clap::{Args, Subcommand, ValueEnum};
-use supermusr_common::{Channel, Intensity, Time};
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+use supermusr_common::{Channel, DigitizerId, Intensity, Time};
+use thiserror::Error;
 
-use chrono::{DateTime, Utc};
+use crate::{
+    finder::SearchMode,
+    graphics::FileFormat,
+    messages::{
+        DigitiserMetadata, DigitiserTrace, IntensityTransform, Polarity, ThresholdDetector, Trace,
+        UnitConversion,
+    },
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
 
 #[derive(Clone, Debug, Args)]
 pub(crate) struct Topics {
@@ -14,7 +25,7 @@ pub(crate) struct Topics {
     pub(crate) digitiser_event_topic: String,
 }
 
-#[derive(Clone, Debug, Args)]
+#[derive(Clone, Debug, Default, Args)]
 pub(crate) struct UserBounds {
     /// Minimum time bin to graph, derived from input if left unspecified.
     #[clap(long)]
@@ -33,15 +44,217 @@ pub(crate) struct UserBounds {
     pub(crate) intensity_max: Option<Intensity>,
 }
 
-/*
+/// Conversion of traces into physical units, for graphing.
+#[derive(Clone, Debug, Default, Args)]
+pub(crate) struct Units {
+    /// If set, graphs are labelled in nanoseconds and millivolts, rather than sample indices and raw intensities.
+    #[clap(long)]
+    pub(crate) physical_units: bool,
+
+    /// The ADC scale, in millivolts per count, of digitisers without their own calibration.
+    #[clap(long, default_value = "1.0", value_parser = parse_adc_scale)]
+    pub(crate) adc_scale: f64,
+
+    /// The ADC offset, in millivolts, of digitisers without their own calibration.
+    #[clap(long, default_value = "0.0")]
+    pub(crate) adc_offset: f64,
+
+    /// The ADC calibrations of particular digitisers, as a list of `id:scale:offset`, e.g. `4:0.122:-500,5:0.122:0`.
+    #[clap(long, value_delimiter = ',')]
+    pub(crate) adc_calibrations: Vec<AdcCalibration>,
+}
+
+impl Units {
+    /// Returns the conversion for the digitiser's trace, or [None] if physical units are not enabled,
+    /// or the trace has no sample rate.
+    pub(crate) fn conversion(
+        &self,
+        id: DigitizerId,
+        trace: &DigitiserTrace,
+    ) -> Option<UnitConversion> {
+        if !self.physical_units {
+            return None;
+        }
+        let (scale, offset) = self
+            .adc_calibrations
+            .iter()
+            .find(|calibration| calibration.id == id)
+            .map_or((self.adc_scale, self.adc_offset), |calibration| {
+                (calibration.scale, calibration.offset)
+            });
+        UnitConversion::new(trace.sample_rate?, scale, offset)
+    }
+}
+
+/// Parses an ADC scale, which must be finite and positive, as intensities are divided by it,
+/// and the bounds of graphs are converted by it without reordering their limits.
+fn parse_adc_scale(s: &str) -> anyhow::Result<f64> {
+    let scale: f64 = s.trim().parse()?;
+    if !(scale.is_finite() && scale > 0.0) {
+        anyhow::bail!("ADC scale must be positive, found `{s}`");
+    }
+    Ok(scale)
+}
+
+/// The ADC scale and offset of a particular digitiser.
+#[derive(Clone, Debug)]
+pub(crate) struct AdcCalibration {
+    id: DigitizerId,
+    /// In millivolts per count.
+    scale: f64,
+    /// In millivolts.
+    offset: f64,
+}
+
+impl FromStr for AdcCalibration {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.trim().split(':').collect();
+        let [id, scale, offset] = fields.as_slice() else {
+            anyhow::bail!("Expected `id:scale:offset`, found `{s}`");
+        };
+        Ok(Self {
+            id: id.parse()?,
+            scale: parse_adc_scale(scale)?,
+            offset: offset.parse()?,
+        })
+    }
+}
+
+/// The scale of the intensity axis of graphs, which can also be toggled from the display panel.
+///
+/// Other than on a linear scale, the intensity axis is fitted to the rescaled traces, and intensity bounds are not applied.
+#[derive(Clone, Debug, Args)]
+pub(crate) struct IntensityScale {
+    /// If set, the baseline of each trace is subtracted from its intensities.
+    #[clap(long)]
+    pub(crate) subtract_baseline: bool,
+
+    /// The number of samples at the start of each trace, before the trigger, from which its baseline is estimated.
+    #[clap(long, default_value = "100")]
+    pub(crate) baseline_window: usize,
+
+    /// If set, intensities are negated, for detectors whose pulses are negative-going.
+    #[clap(long)]
+    pub(crate) invert_intensity: bool,
+
+    /// If set, intensities are graphed on a symmetric logarithmic scale.
+    #[clap(long)]
+    pub(crate) log_intensity: bool,
+}
+
+impl IntensityScale {
+    /// Returns true if intensities are graphed as they are.
+    pub(crate) fn is_linear(&self) -> bool {
+        !(self.subtract_baseline || self.invert_intensity || self.log_intensity)
+    }
+
+    /// Returns the transform of the trace's intensities, once they are in the units of the graph.
+    ///
+    /// The baseline is the mean of the samples in the pre-trigger window.
+    pub(crate) fn transform(
+        &self,
+        trace: &Trace,
+        conversion: Option<&UnitConversion>,
+    ) -> IntensityTransform {
+        let baseline = if self.subtract_baseline {
+            let window = &trace[..self.baseline_window.min(trace.len())];
+            let mean = if window.is_empty() {
+                0.0
+            } else {
+                window.iter().map(|&v| v as f64).sum::<f64>() / window.len() as f64
+            };
+            conversion.map_or(mean, |conversion| conversion.intensity(mean))
+        } else {
+            0.0
+        };
+        IntensityTransform::new(baseline, self.invert_intensity, self.log_intensity)
+    }
+}
+
+/// The settings of the local event detector, with which threshold settings are previewed against the traces found.
+///
+/// The events it detects are graphed alongside those of the broker, and counted against them.
+#[derive(Clone, Debug, Args)]
+pub(crate) struct DetectorSettings {
+    /// If set, events are detected locally where each trace goes this far from the baseline, in the direction of the polarity.
+    #[clap(long)]
+    pub(crate) detector_threshold: Option<Intensity>,
+
+    /// The direction in which the detectors' pulses go from the baseline, `positive` or `negative`.
+    #[clap(long, default_value = "positive")]
+    pub(crate) detector_polarity: Polarity,
+
+    /// The raw intensity of each trace when there is no pulse, from which the threshold is measured.
+    #[clap(long, default_value = "0")]
+    pub(crate) detector_baseline: Intensity,
+
+    /// The number of consecutive samples a trace must stay at or above the threshold for an event to be detected.
+    #[clap(long, default_value = "1")]
+    pub(crate) detector_duration: usize,
+
+    /// The number of samples after each detected event during which no other is detected.
+    #[clap(long, default_value = "0")]
+    pub(crate) detector_cool_off: usize,
+
+    /// Detected events match those of the broker if they are at most this many samples apart.
+    #[clap(long, default_value = "2")]
+    pub(crate) match_tolerance: Time,
+}
+
+impl DetectorSettings {
+    /// Returns the detector, if a threshold is set.
+    pub(crate) fn detector(&self) -> Option<ThresholdDetector> {
+        self.detector_threshold.map(|threshold| {
+            ThresholdDetector::new(
+                self.detector_polarity,
+                self.detector_baseline,
+                threshold,
+                self.detector_duration,
+                self.detector_cool_off,
+            )
+        })
+    }
+}
+
 #[derive(Clone, Subcommand)]
 pub(crate) enum Mode {
-    /// Outputs image to file.
+    /// Searches without a terminal, and outputs every channel found to file.
     File(OutputToFile),
     // /// Outputs image to server.
     //Server(OutputToFile),
 }
-    */
+
+#[derive(Clone, Args)]
+pub(crate) struct OutputToFile {
+    /// The search mode to use, `Live` searches cannot be output to file.
+    #[clap(long, default_value = "FromEnd")]
+    pub(crate) search_mode: SearchMode,
+
+    /// The number of traces to collect.
+    #[clap(long, default_value = "1")]
+    pub(crate) number: usize,
+
+    /// The directory in which to save the files,
+    /// each is saved as `<path>/<frame timestamp>/<channel>.<format>`,
+    /// other than `nxs` files, of which one is saved holding every trace found
+    /// (only available with the `nexus` feature).
+    #[clap(long)]
+    pub(crate) path: PathBuf,
+
+    /// The file format, either an image format or a numeric data format.
+    #[clap(long, default_value = "svg")]
+    pub(crate) format: FileFormat,
+
+    /// The width of each image.
+    #[clap(long, default_value = "800")]
+    pub(crate) width: u32,
+
+    /// The height of each image.
+    #[clap(long, default_value = "600")]
+    pub(crate) height: u32,
+}
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
 pub(crate) enum CollectType {
@@ -68,14 +281,95 @@ pub(crate) struct Select {
     #[clap(flatten)]
     pub(crate) step: Steps,
 
-    // /// The digitiser Id to search for.
-    // #[clap(long)]
-    // pub(crate) digitiser_id: DigitizerId,
-    /// The channel to search for.
+    #[clap(flatten)]
+    pub(crate) limits: Limits,
+
+    #[clap(flatten)]
+    pub(crate) pairing: Pairing,
+
+    #[clap(flatten)]
+    pub(crate) filter: MetadataFilter,
+
+    /// The digitiser ids to search for, as a list of values and ranges, e.g. `1,3,5-8`.
+    #[clap(long, default_value = "4")]
+    pub(crate) digitiser_ids: ValueList<DigitizerId>,
+
+    /// The channels to search for, as a list of values and ranges, e.g. `1,3,5-8`.
+    #[clap(long, default_value = "1")]
+    pub(crate) channels: ValueList<Channel>,
+
+    /// The frame numbers to search for, as a list of values and ranges, e.g. `1,3,5-8`.
+    #[clap(long, default_value = "1")]
+    pub(crate) frame_numbers: ValueList<u32>,
+}
+
+/// Limits which end a search early, if it has not yet finished.
+#[derive(Clone, Debug, Args)]
+pub(crate) struct Limits {
+    /// The maximum time, in seconds, that a search can take.
+    #[clap(long, default_value = "30")]
+    pub(crate) search_timeout: u64,
+
+    /// The maximum number of messages that a search can scan.
+    #[clap(long, default_value = "100000")]
+    pub(crate) max_messages_scanned: usize,
+}
+
+/// Restricts a search to frames whose metadata matches every field which is set.
+#[derive(Clone, Debug, Default, Args)]
+pub(crate) struct MetadataFilter {
+    /// Only find frames of this period.
+    #[clap(long)]
+    pub(crate) period_number: Option<u64>,
+
+    /// Only find frames whose running flag is this value.
     #[clap(long)]
-    pub(crate) channel: Channel,
+    pub(crate) running: Option<bool>,
+
+    /// Only find frames whose veto flags are exactly this value, e.g. `0` for frames which were not vetoed.
+    #[clap(long)]
+    pub(crate) veto_flags: Option<u16>,
+
+    /// Only find frames with at least this many protons per pulse.
+    #[clap(long)]
+    pub(crate) min_protons_per_pulse: Option<u8>,
+}
+
+impl MetadataFilter {
+    /// Returns true if the metadata matches every field which is set.
+    pub(crate) fn matches(&self, metadata: &DigitiserMetadata) -> bool {
+        self.period_number
+            .is_none_or(|period_number| metadata.period_number == period_number)
+            && self
+                .running
+                .is_none_or(|running| metadata.running == running)
+            && self
+                .veto_flags
+                .is_none_or(|veto_flags| metadata.veto_flags == veto_flags)
+            && self
+                .min_protons_per_pulse
+                .is_none_or(|ppp| metadata.protons_per_pulse >= ppp)
+    }
 }
 
+/// Rules for pairing event lists with the traces they were formed from.
+#[derive(Clone, Debug, Args)]
+pub(crate) struct Pairing {
+    /// The maximum difference, in nanoseconds, between the timestamps of a trace and an event list
+    /// of the same frame, period and digitiser, for them to be paired when their timestamps differ.
+    #[clap(long, default_value = "1000000")]
+    pub(crate) pairing_tolerance_ns: i64,
+}
+
+impl Pairing {
+    /// The maximum difference between the timestamps of a paired trace and event list.
+    pub(crate) fn tolerance(&self) -> TimeDelta {
+        TimeDelta::nanoseconds(self.pairing_tolerance_ns)
+    }
+}
+
+/// Tuning parameters for stepping backwards through a topic,
+/// used when seeking a timestamp on a partition for which the broker has no time index.
 #[derive(Clone, Debug, Args)]
 pub(crate) struct Steps {
     /// The min step size that the Kafka searcher takes backwards in time when seeking the timestamp.
@@ -90,3 +384,218 @@ pub(crate) struct Steps {
     #[clap(long, default_value = "5")]
     pub(crate) num_step_passes: u32,
 }
+
+/// A value which may be left unset, written as `any` when it is.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Optional<T>(pub(crate) Option<T>);
+
+impl<T: FromStr> FromStr for Optional<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() || s == "any" {
+            Ok(Self(None))
+        } else {
+            s.parse().map(Some).map(Self)
+        }
+    }
+}
+
+impl<T: Display> Display for Optional<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(value) => write!(f, "{value}"),
+            None => write!(f, "any"),
+        }
+    }
+}
+
+/// Errors which can occur when parsing a [ValueList].
+#[derive(Debug, Error)]
+pub(crate) enum ValueListError {
+    #[error("List is empty")]
+    Empty,
+    #[error("Invalid value: {0}")]
+    InvalidValue(String),
+    #[error("Invalid range: {0}")]
+    InvalidRange(String),
+    #[error("Range too large: {0}")]
+    RangeTooLarge(String),
+}
+
+/// A sorted list of distinct values, written as comma separated values and inclusive ranges, e.g. `1,3,5-8`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ValueList<T>(Vec<T>);
+
+impl<T> ValueList<T> {
+    /// The most values a list can hold, so that a mistyped range cannot expand to billions of values,
+    /// while a range of frame numbers can still span a whole run, of around a day at 50 Hz.
+    pub(crate) const MAX_VALUES: u64 = 1 << 22;
+
+    /// The values of the list.
+    pub(crate) fn values(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> FromStr for ValueList<T>
+where
+    T: FromStr + Copy + Ord + Into<u64> + TryFrom<u64>,
+{
+    type Err = ValueListError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<T>()
+                .map_err(|_| ValueListError::InvalidValue(value.trim().to_owned()))
+        };
+
+        let mut values = Vec::<T>::new();
+        for item in s.split(',').filter(|item| !item.trim().is_empty()) {
+            if let Some((start, end)) = item.split_once('-') {
+                let (start, end): (u64, u64) = (parse(start)?.into(), parse(end)?.into());
+                if start > end {
+                    return Err(ValueListError::InvalidRange(item.trim().to_owned()));
+                }
+                if end - start + 1 > Self::MAX_VALUES.saturating_sub(values.len() as u64) {
+                    return Err(ValueListError::RangeTooLarge(item.trim().to_owned()));
+                }
+                values.extend((start..=end).filter_map(|v| T::try_from(v).ok()));
+            } else {
+                values.push(parse(item)?);
+            }
+        }
+        if values.is_empty() {
+            return Err(ValueListError::Empty);
+        }
+        values.sort();
+        values.dedup();
+        Ok(Self(values))
+    }
+}
+
+impl<T> Display for ValueList<T>
+where
+    T: Copy + Display + PartialEq + Into<u64>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Consecutive values are written as a range.
+        let mut runs = Vec::<(T, T)>::new();
+        for &value in &self.0 {
+            match runs.last_mut() {
+                Some((_, end)) if Into::<u64>::into(*end) + 1 == Into::<u64>::into(value) => {
+                    *end = value
+                }
+                _ => runs.push((value, value)),
+            }
+        }
+        let items: Vec<String> = runs
+            .into_iter()
+            .map(|(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{start}-{end}")
+                }
+            })
+            .collect();
+        write!(f, "{}", items.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn value_list_ranges() {
+        let list = ValueList::<u32>::from_str("1,3,5-8").unwrap();
+        assert_eq!(list.values(), &[1, 3, 5, 6, 7, 8]);
+        assert_eq!(list.to_string(), "1,3,5-8");
+
+        let list = ValueList::<u8>::from_str(" 7 , 2-3 ,").unwrap();
+        assert_eq!(list.values(), &[2, 3, 7]);
+    }
+
+    #[test]
+    fn value_list_duplicates() {
+        let list = ValueList::<u32>::from_str("4,2-5,4,5").unwrap();
+        assert_eq!(list.values(), &[2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn value_list_invalid() {
+        assert!(matches!(
+            ValueList::<u32>::from_str("8-5"),
+            Err(ValueListError::InvalidRange(_))
+        ));
+        assert!(matches!(
+            ValueList::<u32>::from_str("1,x"),
+            Err(ValueListError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            ValueList::<u8>::from_str("1-300"),
+            Err(ValueListError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            ValueList::<u32>::from_str(" , "),
+            Err(ValueListError::Empty)
+        ));
+    }
+
+    #[test]
+    fn value_list_cap() {
+        // A whole run of frame numbers is within the cap.
+        let list = ValueList::<u32>::from_str("1-200000").unwrap();
+        assert_eq!(list.values().len(), 200_000);
+
+        let max = ValueList::<u32>::MAX_VALUES;
+        assert!(ValueList::<u32>::from_str(&format!("1-{max}")).is_ok());
+        assert!(matches!(
+            ValueList::<u32>::from_str(&format!("1-{}", max + 1)),
+            Err(ValueListError::RangeTooLarge(_))
+        ));
+        assert!(matches!(
+            ValueList::<u32>::from_str(&format!("0,1-{max}")),
+            Err(ValueListError::RangeTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn pairing_tolerance() {
+        #[derive(Parser)]
+        struct Cli {
+            #[clap(flatten)]
+            pairing: Pairing,
+        }
+
+        let cli = Cli::parse_from(["cli"]);
+        assert_eq!(cli.pairing.tolerance(), TimeDelta::milliseconds(1));
+        let cli = Cli::parse_from(["cli", "--pairing-tolerance-ns", "250"]);
+        assert_eq!(cli.pairing.tolerance(), TimeDelta::nanoseconds(250));
+    }
+
+    #[test]
+    fn adc_scale() {
+        assert_eq!(parse_adc_scale("0.122").unwrap(), 0.122);
+        assert_eq!(parse_adc_scale(" 2 ").unwrap(), 2.0);
+        for scale in ["0", "-0.122", "inf", "NaN", "scale"] {
+            assert!(parse_adc_scale(scale).is_err(), "{scale}");
+        }
+    }
+
+    #[test]
+    fn adc_calibration() {
+        let calibration = AdcCalibration::from_str("4:0.122:-500").unwrap();
+        assert_eq!(calibration.id, 4);
+        assert_eq!(calibration.scale, 0.122);
+        assert_eq!(calibration.offset, -500.0);
+        assert!(AdcCalibration::from_str("4:-0.122:-500").is_err());
+        assert!(AdcCalibration::from_str("4:0.122").is_err());
+    }
+}
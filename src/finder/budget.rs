@@ -0,0 +1,69 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::finder::{SearchStatus, SearchTarget};
+
+/// Tracks how much of a search's time and message limits have been used.
+///
+/// Clones share the same count of scanned messages, so every [Searcher] created by a search draws on the same budget.
+///
+/// [Searcher]: crate::finder::searcher::Searcher
+#[derive(Clone)]
+pub(crate) struct SearchBudget {
+    /// The time limit of the search.
+    timeout: Duration,
+    /// The time at which the search should end.
+    deadline: Instant,
+    /// The maximum number of messages the search can scan.
+    max_messages_scanned: usize,
+    /// The number of messages scanned so far.
+    messages_scanned: Arc<AtomicUsize>,
+}
+
+impl SearchBudget {
+    /// Creates a new budget from the target's limits, whose clock starts now.
+    ///
+    /// # Attributes
+    /// - target: the search target whose limits to use.
+    pub(crate) fn new(target: &SearchTarget) -> Self {
+        Self {
+            timeout: target.timeout,
+            deadline: Instant::now() + target.timeout,
+            max_messages_scanned: target.max_messages_scanned,
+            messages_scanned: Default::default(),
+        }
+    }
+
+    /// The time at which the search should end.
+    pub(crate) fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Records that a message has been scanned.
+    pub(crate) fn scan(&self) {
+        self.messages_scanned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// If either limit has been reached, returns the status explaining which one.
+    pub(crate) fn exhausted(&self) -> Option<SearchStatus> {
+        if Instant::now() >= self.deadline {
+            Some(SearchStatus::TimedOut(self.timeout.as_secs()))
+        } else if self.messages_scanned.load(Ordering::Relaxed) >= self.max_messages_scanned {
+            Some(SearchStatus::ScanLimitReached(self.max_messages_scanned))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if either limit has been reached.
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.exhausted().is_some()
+    }
+}
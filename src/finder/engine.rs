@@ -1,11 +1,14 @@
 use chrono::Duration;
 use rdkafka::consumer::{BaseConsumer, StreamConsumer};
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
 use tracing::{error, instrument};
 
 use crate::{
     finder::{
-        task::{SearchByTimestamp, SearchFromEnd, SearchTask},
+        task::{SearchByFrameNumber, SearchByTimestamp, SearchFromEnd, SearchLive, SearchTask},
         MessageFinder, SearchMode, SearchResults, SearchStatus, SearchTarget,
     },
     Select, Topics,
@@ -23,7 +26,11 @@ pub(crate) struct SearchEngine {
     /// it is passed back via this channel.
     send_init: mpsc::Sender<(StreamConsumer, SearchTarget)>,
     recv_results: mpsc::Receiver<(StreamConsumer, SearchResults)>,
+    /// Whilst a live search is in progress, snapshots of its results are sent via this channel.
+    recv_live_results: mpsc::Receiver<SearchResults>,
     recv_status: mpsc::Receiver<SearchStatus>,
+    /// Set to `true` to halt the search in progress, and reset to `false` when a new search begins.
+    send_halt: watch::Sender<bool>,
     status: Option<SearchStatus>,
     //
     results: Option<SearchResults>,
@@ -40,12 +47,16 @@ impl SearchEngine {
 
         let (send_init, mut recv_init) = mpsc::channel(1);
         let (send_results, recv_results) = mpsc::channel(1);
+        let (send_live_results, recv_live_results) = mpsc::channel(1);
         let (send_status, recv_status) = mpsc::channel(1);
+        let (send_halt, recv_halt) = watch::channel(false);
         Self {
             consumer: Some(consumer),
             send_init,
             recv_results,
+            recv_live_results,
             recv_status,
+            send_halt,
             target: None,
             status: None,
             results: None,
@@ -58,6 +69,7 @@ impl SearchEngine {
                             SearchTask::<SearchFromEnd>::new(
                                 consumer,
                                 &send_status,
+                                &recv_halt,
                                 &select,
                                 &topics,
                             )
@@ -68,12 +80,35 @@ impl SearchEngine {
                             SearchTask::<SearchByTimestamp>::new(
                                 consumer,
                                 &send_status,
+                                &recv_halt,
+                                &select,
+                                &topics,
+                            )
+                            .search(target)
+                            .await
+                        }
+                        SearchMode::ByFrameNumbers => {
+                            SearchTask::<SearchByFrameNumber>::new(
+                                consumer,
+                                &send_status,
+                                &recv_halt,
                                 &select,
                                 &topics,
                             )
                             .search(target)
                             .await
                         }
+                        SearchMode::Live => {
+                            SearchTask::<SearchLive>::new(
+                                consumer,
+                                &send_status,
+                                &recv_halt,
+                                &select,
+                                &topics,
+                            )
+                            .search(target, &send_live_results)
+                            .await
+                        }
                     };
 
                     send_results.send((consumer, results)).await.expect("");
@@ -99,6 +134,16 @@ impl MessageFinder for SearchEngine {
         self.consumer.is_some()
     }
 
+    #[instrument(skip_all)]
+    fn halt_search(&mut self) -> bool {
+        // The consumer is only absent whilst a search is in progress.
+        let is_searching = self.consumer.is_none();
+        if is_searching {
+            self.send_halt.send_replace(true);
+        }
+        is_searching
+    }
+
     fn status(&mut self) -> Option<SearchStatus> {
         self.status.take()
     }
@@ -110,6 +155,7 @@ impl MessageFinder for SearchEngine {
     async fn update(&mut self) {
         if let Some(target) = self.target.take() {
             if let Some(consumer) = self.consumer.take() {
+                self.send_halt.send_replace(false);
                 if let Err(_) = self.send_init.send((consumer, target)).await {
                     error!("send_init failed");
                 }
@@ -125,11 +171,11 @@ impl MessageFinder for SearchEngine {
             }
         }
 
-        /*if !self.recv_halt.is_empty() {
-            if let Some(consumer) = self.recv_halt.recv().await {
-                self.consumer = Some(consumer);
+        if !self.recv_live_results.is_empty() {
+            if let Some(results) = self.recv_live_results.recv().await {
+                self.results = Some(results);
             }
-        }*/
+        }
 
         if !self.recv_status.is_empty() {
             if let Some(status) = self.recv_status.recv().await {
@@ -137,4 +183,29 @@ impl MessageFinder for SearchEngine {
             }
         }
     }
+
+    async fn wait_for_update(&mut self) {
+        // A new search must first be sent to the task.
+        if self.target.is_some() {
+            self.update().await;
+            return;
+        }
+
+        // A status is sent before the results which follow it, so is taken first,
+        // lest the final status of a search be lost once its results are taken.
+        tokio::select! {
+            biased;
+            Some(status) = self.recv_status.recv() => {
+                self.status = Some(status);
+            }
+            Some((consumer, results)) = self.recv_results.recv() => {
+                self.consumer = Some(consumer);
+                self.results = Some(results);
+            }
+            Some(results) = self.recv_live_results.recv() => {
+                self.results = Some(results);
+            }
+            else => error!("Search task has ended"),
+        }
+    }
 }
@@ -1,3 +1,4 @@
+mod budget;
 mod engine;
 mod searcher;
 mod task;
@@ -7,6 +8,7 @@ use strum::{Display, EnumIter, EnumString};
 use supermusr_common::{Channel, DigitizerId};
 
 use crate::{
+    cli_structs::MetadataFilter,
     messages::{Cache, EventListMessage, FBMessage, TraceMessage},
     Timestamp,
 };
@@ -19,6 +21,8 @@ pub(crate) enum SearchMode {
     FromEnd,
     ByChannels,
     ByDigitiserIds,
+    ByFrameNumbers,
+    Live,
 }
 
 #[derive(Default)]
@@ -31,6 +35,10 @@ pub(crate) enum SearchStatus {
     EventListSearchInProgress(u32),
     EventListSearchFinished,
     Halted,
+    /// The search ran for longer than its timeout, in seconds.
+    TimedOut(u64),
+    /// The search scanned its maximum number of messages.
+    ScanLimitReached(usize),
     Successful,
 }
 
@@ -63,23 +71,58 @@ pub(crate) struct SearchTarget {
     pub(crate) timestamp: Timestamp,
     pub(crate) channels: Vec<Channel>,
     pub(crate) digitiser_ids: Vec<DigitizerId>,
+    /// The sorted frame numbers to search for in [SearchMode::ByFrameNumbers] mode, of which the first [Self::number] are sought.
+    pub(crate) frame_numbers: Vec<u32>,
     pub(crate) number: usize,
+    /// The maximum time the search can take.
+    pub(crate) timeout: std::time::Duration,
+    /// The maximum number of messages the search can scan.
+    pub(crate) max_messages_scanned: usize,
+    /// Restricts the search to frames with matching metadata.
+    pub(crate) filter: MetadataFilter,
 }
 
 impl SearchTarget {
+    /// Returns true if the message's frame metadata matches [Self::filter].
+    pub(crate) fn filter_metadata<'a, M: FBMessage<'a>>(&self, msg: &M) -> bool {
+        self.filter.matches(msg.metadata())
+    }
+
+    /// Returns the frame numbers sought, which are the first [Self::number] of [Self::frame_numbers].
+    pub(crate) fn frame_numbers_sought(&self) -> &[u32] {
+        &self.frame_numbers[..self.number.min(self.frame_numbers.len())]
+    }
+
+    /// In [SearchMode::ByChannels] mode, returns true if the trace contains any of the target's channels,
+    /// otherwise returns true if the trace is from any of the target's digitisers.
+    ///
+    /// In either case, the trace must also match [Self::filter].
     pub(crate) fn filter_trace_by_channel_and_digtiser_id(&self, msg: &TraceMessage) -> bool {
-        //self.channels.
-        //    iter()
-        //    .any(|&c| msg.has_channel(c)) ||
-        self.digitiser_ids
-            .iter()
-            .any(|&d: &u8| msg.digitiser_id() == d)
+        self.filter_metadata(msg)
+            && match self.mode {
+                SearchMode::ByChannels => self.channels.iter().any(|&c| msg.has_channel(c)),
+                _ => self
+                    .digitiser_ids
+                    .iter()
+                    .any(|&d: &u8| msg.digitiser_id() == d),
+            }
     }
 
+    /// In [SearchMode::ByChannels] mode, returns true for any event list, as an event list omits the channels
+    /// which had no events, so cannot be told apart by channel. Those not paired with a trace are then removed
+    /// by [Cache::remove_unpaired_events].
+    /// Otherwise returns true if the event list is from any of the target's digitisers.
+    ///
+    /// In either case, the event list must also match [Self::filter].
     pub(crate) fn filter_eventlist_digtiser_id(&self, msg: &EventListMessage) -> bool {
-        self.digitiser_ids
-            .iter()
-            .any(|&d: &u8| msg.digitiser_id() == d)
+        self.filter_metadata(msg)
+            && match self.mode {
+                SearchMode::ByChannels => true,
+                _ => self
+                    .digitiser_ids
+                    .iter()
+                    .any(|&d: &u8| msg.digitiser_id() == d),
+            }
     }
 }
 
@@ -88,9 +131,19 @@ pub(crate) trait MessageFinder {
 
     fn init_search(&mut self, target: SearchTarget) -> bool;
 
+    /// Halts the search in progress, returning true if there was one to halt.
+    ///
+    /// The halted search still returns the results it collected before it was halted.
+    fn halt_search(&mut self) -> bool;
+
     fn status(&mut self) -> Option<SearchStatus>;
 
     fn results(&mut self) -> Option<SearchResults>;
 
     async fn update(&mut self);
+
+    /// Waits until the search in progress sends a status or results, and takes it as [Self::update] does.
+    ///
+    /// Unlike polling [Self::update], this does not hold up a search which emits many statuses.
+    async fn wait_for_update(&mut self);
 }
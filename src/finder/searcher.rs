@@ -2,13 +2,18 @@ use std::time::Duration;
 
 use rdkafka::{
     consumer::{BaseConsumer, Consumer, StreamConsumer},
+    message::BorrowedMessage,
     util::Timeout,
     TopicPartitionList,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tracing::instrument;
 
-use crate::{finder::SearchStatus, messages::FBMessage, Timestamp};
+use crate::{
+    finder::{budget::SearchBudget, SearchStatus},
+    messages::FBMessage,
+    Timestamp,
+};
 
 /// Object to search through the broker from a given offset, on a given topic, for messages of type `M`.
 pub(crate) struct Searcher<'a, M, C> {
@@ -16,38 +21,53 @@ pub(crate) struct Searcher<'a, M, C> {
     consumer: &'a C,
     /// Topic to search on.
     topic: String,
+    /// Partition of [Self::topic] to search on.
+    partition: i32,
     /// Current offset.
     offset: i64,
     /// Send channel, along which status messages should be sent.
     send_status: mpsc::Sender<SearchStatus>,
+    /// Receive channel, which is set to `true` when the search should be halted.
+    halt: watch::Receiver<bool>,
+    /// The limits of the search, shared with every other searcher of the search.
+    budget: SearchBudget,
     /// Results accumulate here.
     results: Vec<M>,
 }
 
 impl<'a, M, C: Consumer> Searcher<'a, M, C> {
-    /// Creates a new instance, and assigns the given topic to the broker's consumer.
+    /// Creates a new instance, and assigns the given topic partition to the broker's consumer.
     ///
     /// # Attributes
     /// - consumer: the broker's consumer to use.
     /// - topic: the topic to search on.
-    /// - offset: the offset to search from.
+    /// - partition: the partition of the topic to search on.
+    /// - offset: the offset, from the end of the partition, to search from.
     /// - send_status: send channel, along which status messages should be sent.
+    /// - halt: receive channel, which is set to `true` when the search should be halted.
+    /// - budget: the limits of the search.
     #[instrument(skip_all)]
     pub(crate) fn new(
         consumer: &'a C,
         topic: &str,
+        partition: i32,
         offset: i64,
         send_status: mpsc::Sender<SearchStatus>,
+        halt: watch::Receiver<bool>,
+        budget: SearchBudget,
     ) -> Self {
         let mut tpl = TopicPartitionList::with_capacity(1);
-        tpl.add_partition_offset(topic, 0, rdkafka::Offset::End)
+        tpl.add_partition_offset(topic, partition, rdkafka::Offset::OffsetTail(offset))
             .expect("");
         consumer.assign(&tpl).expect("");
         Self {
             consumer,
             offset,
             topic: topic.to_owned(),
+            partition,
             send_status,
+            halt,
+            budget,
             results: Default::default(),
         }
     }
@@ -69,6 +89,15 @@ impl<'a, M, C: Consumer> Searcher<'a, M, C> {
         }
     }
 
+    #[instrument(skip_all)]
+    /// Consumer the searcher and create a time index iterator.
+    pub(crate) fn iter_time_index(self) -> TimeIndexIter<'a, M, C> {
+        TimeIndexIter {
+            inner: self,
+            range: None,
+        }
+    }
+
     #[instrument(skip_all)]
     /// Consumer the searcher and create a forward iterator.
     pub(crate) fn iter_forward(self) -> ForwardSearchIter<'a, M, C> {
@@ -83,9 +112,9 @@ impl<'a, M, C: Consumer> Searcher<'a, M, C> {
         self.offset = offset;
     }
 
-    /// Gets the offset.
-    pub(crate) fn get_offset(&self) -> i64 {
-        self.offset
+    /// Returns true if the search has been halted by the user, or has exhausted its budget.
+    pub(crate) fn is_stopped(&self) -> bool {
+        *self.halt.borrow() || self.budget.is_exhausted()
     }
 }
 
@@ -101,32 +130,63 @@ impl<'a, M> Searcher<'a, M, StreamConsumer>
 where
     M: FBMessage<'a>,
 {
+    /// Moves the consumer to the given offset, so the next message received is the one at `offset`.
+    ///
+    /// # Attributes
+    /// - offset: the offset to seek to.
     #[instrument(skip_all)]
-    async fn message(&mut self, offset: i64) -> Option<M> {
+    fn seek(&self, offset: rdkafka::Offset) {
         self.consumer
             .seek(
                 &self.topic,
-                0,
-                rdkafka::Offset::OffsetTail(offset),
+                self.partition,
+                offset,
                 Duration::from_millis(1),
             )
             .expect("");
+    }
 
-        let msg: Option<M> = self
-            .consumer
-            .recv()
-            .await
-            .ok()
-            .and_then(FBMessage::from_borrowed_message);
+    /// Receives the message at the given offset, from the end of the partition.
+    #[instrument(skip_all)]
+    async fn message(&mut self, offset: i64) -> Option<M> {
+        self.message_at(rdkafka::Offset::OffsetTail(offset)).await
+    }
 
+    /// Receives the next message from the consumer,
+    /// or returns [None] if the search is halted, or exhausts its budget first.
+    #[instrument(skip_all)]
+    async fn recv(&mut self) -> Option<BorrowedMessage<'a>> {
+        if self.budget.is_exhausted() {
+            return None;
+        }
+        let consumer = self.consumer;
+        let msg = tokio::select! {
+            msg = consumer.recv() => msg.ok(),
+            _ = self.halt.wait_for(|&halt| halt) => None,
+            _ = tokio::time::sleep_until(self.budget.deadline()) => None,
+        };
+        if msg.is_some() {
+            self.budget.scan();
+        }
+        msg
+    }
+
+    /// Receives the message at the given offset.
+    #[instrument(skip_all)]
+    async fn message_at(&mut self, offset: rdkafka::Offset) -> Option<M> {
+        self.seek(offset);
+
+        let msg: Option<M> = self.recv().await.and_then(FBMessage::from_borrowed_message);
+
+        let partition = self.partition;
         match &msg {
             Some(msg) => self.send_status.send(SearchStatus::Text(format!(
-                "Message at offset {offset}: timestamp: {0}",
+                "Message at partition {partition}, offset {offset:?}: timestamp: {0}",
                 msg.timestamp()
             ))),
-            None => self.send_status.send(SearchStatus::Text(
-                format! {"Message at offset {offset} failed"},
-            )),
+            None => self.send_status.send(SearchStatus::Text(format!(
+                "Message at partition {partition}, offset {offset:?} failed"
+            ))),
         }
         .await
         .expect("");
@@ -171,27 +231,170 @@ where
         &mut self,
         f: F,
     ) -> &mut Self {
+        self.backstep_until(|message: &M| f(message.timestamp()))
+            .await
+    }
+
+    /// Repeatedly search the topic backwards, in increments of [Self::step_size],
+    /// until the given predicate of the message is satisfied.
+    ///
+    /// # Attributes
+    /// - f: a predicte taking a message, it should return true when the message is later than the target.
+    #[instrument(skip_all)]
+    pub(crate) async fn backstep_until<F: Fn(&M) -> bool>(&mut self, f: F) -> &mut Self {
         let mut offset = self.inner.offset;
-        let mut earliest = {
-            match self.inner.message(offset).await {
-                Some(message) => message.timestamp(),
-                None => return self,
-            }
-        };
+        match self.inner.message(offset).await {
+            Some(message) if f(&message) => {}
+            _ => return self,
+        }
 
-        while f(earliest) {
+        while !self.inner.is_stopped() {
             let new_offset = offset
                 + self
                     .step_size
                     .expect("Size step should have been set. This should never fail.");
             match self.inner.message(new_offset).await {
+                Some(message) if f(&message) => {
+                    offset = new_offset;
+                }
+                _ => {
+                    break;
+                }
+            }
+        }
+        self.inner.set_offset(offset);
+        self
+    }
+
+    /// Repeatedly search the topic backwards, in increments of [Self::step_size],
+    /// past every message which satisfies the given predicate, to the first which does not.
+    ///
+    /// If the search reaches the start of the partition first, the offset is left as it was.
+    ///
+    /// # Attributes
+    /// - f: a predicate taking a message, it should return true while the message is to be stepped past.
+    #[instrument(skip_all)]
+    pub(crate) async fn backstep_past<F: Fn(&M) -> bool>(&mut self, f: F) -> &mut Self {
+        let step_size = self
+            .step_size
+            .expect("Size step should have been set. This should never fail.");
+        let mut offset = self.inner.offset;
+        while !self.inner.is_stopped() {
+            match self.inner.message(offset).await {
+                Some(message) if f(&message) => offset += step_size,
+                Some(_) => {
+                    self.inner.set_offset(offset);
+                    break;
+                }
+                None => break,
+            }
+        }
+        self
+    }
+}
+
+/// Performs a search on the broker using the broker's time index, refined by a binary search.
+///
+/// Kafka's time index records when each message was produced, rather than the timestamp of the frame it contains,
+/// so the offset it returns is only used as a lower bound, from which the frame timestamps are bisected.
+///
+/// Note this iterator can only move the [Searcher]'s offset, it cannot accumulate results.
+/// Also note, this iterator is not a real iterator (as in it does not implement [Iterator]).
+/// Instead it's methods are inspired by those frequently found in actual iterators.
+pub(crate) struct TimeIndexIter<'a, M, C> {
+    inner: Searcher<'a, M, C>,
+    /// The absolute offsets between which the search takes place, if the broker has a time index.
+    range: Option<(i64, i64)>,
+}
+
+impl<'a, M, C> TimeIndexIter<'a, M, C> {
+    /// Returns true if the broker provided a time index for the searcher's partition.
+    pub(crate) fn has_time_index(&self) -> bool {
+        self.range.is_some()
+    }
+
+    /// Consumes the iterator and returns the original [Searcher] object.
+    pub(crate) fn collect(self) -> Searcher<'a, M, C> {
+        self.inner
+    }
+}
+
+impl<'a, M, C: Consumer> TimeIndexIter<'a, M, C> {
+    /// The time to wait for the broker to respond to offset queries.
+    const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Asks the broker for the offset of the first message produced at or after the given timestamp,
+    /// and sets the range of the search from that offset to the end of the partition.
+    ///
+    /// If the broker has no time index, or either query fails, the range is left unset.
+    ///
+    /// # Attributes
+    /// - timestamp: the time to query the broker's time index with.
+    #[instrument(skip_all)]
+    pub(crate) fn query_time_index(&mut self, timestamp: Timestamp) -> &mut Self {
+        let topic = self.inner.topic.as_str();
+        let partition = self.inner.partition;
+
+        let mut tpl = TopicPartitionList::with_capacity(1);
+        tpl.add_partition_offset(
+            topic,
+            partition,
+            rdkafka::Offset::Offset(timestamp.timestamp_millis()),
+        )
+        .expect("");
+
+        let start = self
+            .inner
+            .consumer
+            .offsets_for_times(tpl, Self::QUERY_TIMEOUT)
+            .ok()
+            .and_then(|tpl| {
+                tpl.find_partition(topic, partition)
+                    .map(|elem| elem.offset())
+            });
+
+        let Ok((_, high)) =
+            self.inner
+                .consumer
+                .fetch_watermarks(topic, partition, Self::QUERY_TIMEOUT)
+        else {
+            return self;
+        };
+
+        self.range = match start {
+            Some(rdkafka::Offset::Offset(start)) => Some((start, high)),
+            // No message was produced at or after the timestamp.
+            Some(rdkafka::Offset::End) => Some((high, high)),
+            _ => None,
+        };
+        self
+    }
+}
+
+impl<'a, M> TimeIndexIter<'a, M, StreamConsumer>
+where
+    M: FBMessage<'a>,
+{
+    /// Bisects the range set by [Self::query_time_index], for the first message
+    /// whose timestamp satisfies the given predicate, and moves the consumer to it.
+    ///
+    /// # Attributes
+    /// - f: a predicate taking a timestamp, it should return true when the timestamp is at or later than the target.
+    #[instrument(skip_all)]
+    pub(crate) async fn bisect_until_time<F: Fn(Timestamp) -> bool>(&mut self, f: F) -> &mut Self {
+        let Some((mut lower, end)) = self.range else {
+            return self;
+        };
+        let mut upper = end;
+
+        while lower < upper && !self.inner.is_stopped() {
+            let mid = lower + (upper - lower) / 2;
+            match self.inner.message_at(rdkafka::Offset::Offset(mid)).await {
                 Some(message) => {
-                    let new_timestamp = message.timestamp();
-                    if f(new_timestamp) {
-                        offset = new_offset;
-                        earliest = new_timestamp;
+                    if f(message.timestamp()) {
+                        upper = mid;
                     } else {
-                        break;
+                        lower = mid + 1;
                     }
                 }
                 None => {
@@ -199,11 +402,22 @@ where
                 }
             }
         }
-        self.inner.set_offset(offset);
+        self.inner.set_offset(end - lower);
+        self.inner.seek(rdkafka::Offset::Offset(lower));
         self
     }
 }
 
+/// What [ForwardSearchIter::acquire_until] does with each message.
+pub(crate) enum Acquire {
+    /// The message is acquired.
+    Take,
+    /// The message is passed over.
+    Skip,
+    /// The message is passed over, and no more are acquired.
+    Stop,
+}
+
 /// Searches on a topic forwards, one message at a time.
 ///
 /// Note this iterator can both move the [Searcher]'s offset and accumulate results.
@@ -230,11 +444,18 @@ where
     /// # Attributes
     /// - f: a predicte taking a timestamp, it should return true when the timestamp is earlier than the target.
     #[instrument(skip_all)]
-    pub(crate) async fn move_until<F: Fn(Timestamp) -> bool>(mut self, f: F) -> Self {
-        while let Ok(msg) = self.inner.consumer.recv().await {
-            if let Some(msg) =
-                FBMessage::from_borrowed_message(msg).filter(|m| f(FBMessage::timestamp(m)))
-            {
+    pub(crate) async fn move_until<F: Fn(Timestamp) -> bool>(self, f: F) -> Self {
+        self.move_until_message(|m: &M| f(m.timestamp())).await
+    }
+
+    /// Steps forward, message by message, until the given predicate of the message is satisfied.
+    ///
+    /// # Attributes
+    /// - f: a predicte taking a message, it should return true when the message is at or later than the target.
+    #[instrument(skip_all)]
+    pub(crate) async fn move_until_message<F: Fn(&M) -> bool>(mut self, f: F) -> Self {
+        while let Some(msg) = self.inner.recv().await {
+            if let Some(msg) = FBMessage::from_borrowed_message(msg).filter(|m| f(m)) {
                 self.message = Some(msg);
                 self.inner
                     .send_status
@@ -250,6 +471,42 @@ where
         self
     }
 
+    /// Steps forward, message by message, from the message found by [Self::move_until_message],
+    /// acquiring each message as decided by the given function, until it returns [Acquire::Stop].
+    ///
+    /// # Attributes
+    /// - f: a function taking a message, it should return whether to acquire it, or to stop.
+    #[instrument(skip_all)]
+    pub(crate) async fn acquire_until<F: FnMut(&M) -> Acquire>(mut self, mut f: F) -> Self {
+        let mut message = self.message.take();
+        while let Some(msg) = message {
+            match f(&msg) {
+                Acquire::Take => self.inner.results.push(msg),
+                Acquire::Skip => {}
+                Acquire::Stop => break,
+            }
+            if self.inner.is_stopped() {
+                break;
+            }
+            message = self
+                .inner
+                .recv()
+                .await
+                .and_then(FBMessage::from_borrowed_message);
+            if let Some(msg) = &message {
+                self.inner
+                    .send_status
+                    .send(SearchStatus::Text(format!(
+                        "Message timestamp: {0}",
+                        msg.timestamp()
+                    )))
+                    .await
+                    .expect("");
+            }
+        }
+        self
+    }
+
     /// Steps forward, message by message, acquiring messages which satisfy the predicate, until the given number of messages are obtained. [TODO]
     ///
     /// # Attributes
@@ -264,20 +521,19 @@ where
 
             let mut messages: Option<M> = self
                 .inner
-                .consumer
                 .recv()
                 .await
-                .ok()
                 .and_then(FBMessage::from_borrowed_message);
 
             for _ in 0..number {
+                if self.inner.is_stopped() {
+                    break;
+                }
                 while let Some(msg) = messages {
                     messages = self
                         .inner
-                        .consumer
                         .recv()
                         .await
-                        .ok()
                         .and_then(FBMessage::from_borrowed_message);
 
                     self.inner
@@ -0,0 +1,39 @@
+use chrono::Utc;
+use rdkafka::consumer::StreamConsumer;
+use tracing::instrument;
+
+use crate::finder::{
+    budget::SearchBudget,
+    task::{SearchTask, SoughtFrames, TaskClass},
+    SearchResults, SearchTarget,
+};
+
+pub(crate) struct SearchByFrameNumber;
+impl TaskClass for SearchByFrameNumber {}
+
+impl<'a> SearchTask<'a, SearchByFrameNumber> {
+    /// Performs a ByFrameNumbers search, acquiring the frames sought from the latest run which has the first of them.
+    ///
+    /// Only the frames whose numbers are sought are acquired, and each partition stops once its frame numbers pass the last of them.
+    /// # Attributes
+    /// - target: what to search for.
+    #[instrument(skip_all)]
+    pub(crate) async fn search(self, target: SearchTarget) -> (StreamConsumer, SearchResults) {
+        let start = Utc::now();
+        let budget = SearchBudget::new(&target);
+
+        let cache = self
+            .search_partitions(
+                &budget,
+                SoughtFrames::FrameNumbers(target.frame_numbers_sought()),
+                |msg| target.filter_metadata(msg),
+                |msg| target.filter_metadata(msg),
+            )
+            .await;
+
+        // Send cache via status
+        self.emit_status(self.finished_status(&budget)).await;
+        let time = Utc::now() - start;
+        (self.consumer, SearchResults { cache, time })
+    }
+}
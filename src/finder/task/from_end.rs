@@ -1,9 +1,10 @@
 use chrono::Utc;
-use rdkafka::consumer::{BaseConsumer, StreamConsumer};
+use rdkafka::consumer::StreamConsumer;
 use tracing::instrument;
 
 use crate::{
     finder::{
+        budget::SearchBudget,
         searcher::Searcher,
         task::{SearchTask, TaskClass},
         SearchResults, SearchStatus, SearchTarget,
@@ -21,6 +22,7 @@ impl<'a> SearchTask<'a, SearchFromEnd> {
     #[instrument(skip_all)]
     pub(crate) async fn search(self, target: SearchTarget) -> (StreamConsumer, SearchResults) {
         let start = Utc::now();
+        let budget = SearchBudget::new(&target);
 
         let mut cache = Cache::default();
 
@@ -28,49 +30,73 @@ impl<'a> SearchTask<'a, SearchFromEnd> {
         self.emit_status(SearchStatus::TraceSearchInProgress(0))
             .await;
 
-        let searcher = Searcher::new(
-            &self.consumer,
-            &self.topics.trace_topic,
-            target.number as i64 + 1,
-            self.send_status.clone(),
-        );
+        let mut trace_results = Vec::<TraceMessage>::new();
+        for partition in self.partitions(&self.topics.trace_topic) {
+            if self.is_stopped(&budget) {
+                break;
+            }
+            let searcher = Searcher::new(
+                &self.consumer,
+                &self.topics.trace_topic,
+                partition,
+                target.number as i64 + 1,
+                self.send_status.clone(),
+                self.halt.clone(),
+                budget.clone(),
+            );
 
-        let trace_results: Vec<TraceMessage> = searcher
-            .iter_forward()
-            .acquire_while(|_| true, target.number)
-            .await
-            .collect()
-            .into();
+            let results: Vec<TraceMessage> = searcher
+                .iter_forward()
+                .move_until(|_| true)
+                .await
+                .acquire_while(|msg| target.filter_metadata(msg), target.number)
+                .await
+                .collect()
+                .into();
+            trace_results.extend(results);
+        }
 
         // Find Digitiser Event Lists
         self.emit_status(SearchStatus::EventListSearchInProgress(0))
             .await;
 
-        let searcher = Searcher::new(
-            &self.consumer,
-            &self.topics.digitiser_event_topic,
-            2 * target.number as i64 + 1,
-            self.send_status.clone(),
-        );
+        let mut eventlist_results = Vec::<EventListMessage>::new();
+        for partition in self.partitions(&self.topics.digitiser_event_topic) {
+            if self.is_stopped(&budget) {
+                break;
+            }
+            let searcher = Searcher::new(
+                &self.consumer,
+                &self.topics.digitiser_event_topic,
+                partition,
+                2 * target.number as i64 + 1,
+                self.send_status.clone(),
+                self.halt.clone(),
+                budget.clone(),
+            );
 
-        let eventlist_results: Vec<EventListMessage> = searcher
-            .iter_forward()
-            .acquire_while(|_| true, 2 * target.number)
-            .await
-            .collect()
-            .into();
+            let results: Vec<EventListMessage> = searcher
+                .iter_forward()
+                .move_until(|_| true)
+                .await
+                .acquire_while(|msg| target.filter_metadata(msg), 2 * target.number)
+                .await
+                .collect()
+                .into();
+            eventlist_results.extend(results);
+        }
 
         for trace in trace_results.iter() {
-            cache.push_trace(&trace.get_unpacked_message().expect(""));
+            cache.push_trace(&trace.get_unpacked_message().expect(""), trace.source());
         }
 
         for eventlist in eventlist_results.iter() {
             cache.push_events(&eventlist.get_unpacked_message().expect(""));
         }
-        cache.attach_event_lists_to_trace();
+        cache.attach_event_lists_to_trace(self.select.pairing.tolerance());
 
         // Send cache via status
-        self.emit_status(SearchStatus::Successful).await;
+        self.emit_status(self.finished_status(&budget)).await;
         let time = Utc::now() - start;
         (self.consumer, SearchResults { cache, time })
     }
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use rdkafka::{
+    consumer::{Consumer, StreamConsumer},
+    Message, TopicPartitionList,
+};
+use tokio::sync::mpsc;
+use tracing::{error, instrument};
+
+use crate::{
+    finder::{
+        task::{SearchTask, TaskClass},
+        SearchResults, SearchStatus, SearchTarget,
+    },
+    messages::{Cache, EventListMessage, FBMessage, TraceMessage},
+};
+
+/// The interval at which snapshots of the cache are sent, which matches the refresh of the display.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(100);
+
+pub(crate) struct SearchLive;
+impl TaskClass for SearchLive {}
+
+impl<'a> SearchTask<'a, SearchLive> {
+    /// Performs a Live search, which streams each new message into the cache until the search is halted.
+    ///
+    /// Only messages which pass the target's filters are kept. The cache is kept to the latest `target.number` frames,
+    /// and, on each tick of [SNAPSHOT_INTERVAL] at which the cache has changed, a snapshot of it is sent
+    /// along `send_results` if the receiver is ready for one.
+    /// As the search only ends when it is halted, the limits of the target are not applied.
+    ///
+    /// # Attributes
+    /// - target: what to search for.
+    /// - send_results: send channel, along which snapshots of the cache should be sent.
+    #[instrument(skip_all)]
+    pub(crate) async fn search(
+        self,
+        target: SearchTarget,
+        send_results: &mpsc::Sender<SearchResults>,
+    ) -> (StreamConsumer, SearchResults) {
+        let start = Utc::now();
+
+        let mut cache = Cache::default();
+
+        // Assign every partition of both topics, from the end.
+        let mut tpl = TopicPartitionList::new();
+        for topic in [&self.topics.trace_topic, &self.topics.digitiser_event_topic] {
+            for partition in self.partitions(topic) {
+                tpl.add_partition_offset(topic, partition, rdkafka::Offset::End)
+                    .expect("");
+            }
+        }
+        if let Err(e) = self.consumer.assign(&tpl) {
+            error!("{e}");
+        }
+
+        self.emit_status(SearchStatus::Text("Live. Press <Esc> to halt.".to_owned()))
+            .await;
+
+        let mut halt = self.halt.clone();
+        let mut snapshot = tokio::time::interval(SNAPSHOT_INTERVAL);
+        // True if the cache has changed since the last snapshot was sent.
+        let mut changed = false;
+        loop {
+            let msg = tokio::select! {
+                msg = self.consumer.recv() => msg,
+                _ = snapshot.tick() => {
+                    if changed {
+                        cache.keep_latest(target.number);
+                        // Only take a snapshot if the previous one has been received.
+                        if let Ok(permit) = send_results.try_reserve() {
+                            cache.attach_event_lists_to_trace(self.select.pairing.tolerance());
+                            let time = Utc::now() - start;
+                            permit.send(SearchResults {
+                                cache: cache.clone(),
+                                time,
+                            });
+                            changed = false;
+                        }
+                    }
+                    continue;
+                }
+                _ = halt.wait_for(|&halt| halt) => break,
+            };
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(e) => {
+                    error!("{e}");
+                    continue;
+                }
+            };
+
+            if msg.topic() == self.topics.trace_topic {
+                if let Some(trace) = TraceMessage::from_borrowed_message(msg)
+                    .filter(|trace| target.filter_trace_by_channel_and_digtiser_id(trace))
+                {
+                    cache.push_trace(&trace.get_unpacked_message().expect(""), trace.source());
+                    changed = true;
+                }
+            } else if let Some(eventlist) = EventListMessage::from_borrowed_message(msg)
+                .filter(|eventlist| target.filter_eventlist_digtiser_id(eventlist))
+            {
+                cache.push_events(&eventlist.get_unpacked_message().expect(""));
+                changed = true;
+            }
+        }
+
+        cache.keep_latest(target.number);
+        cache.attach_event_lists_to_trace(self.select.pairing.tolerance());
+        self.emit_status(SearchStatus::Halted).await;
+        let time = Utc::now() - start;
+        (self.consumer, SearchResults { cache, time })
+    }
+}
@@ -1,23 +1,119 @@
+mod by_frame;
 mod by_timestamp;
-mod capture;
 mod from_end;
+mod live;
 
-use std::marker::PhantomData;
+use std::{collections::BTreeSet, marker::PhantomData, time::Duration};
 
-use rdkafka::consumer::StreamConsumer;
-use tokio::sync::mpsc;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use tokio::sync::{mpsc, watch};
 use tracing::{error, instrument};
 
-use crate::{finder::SearchStatus, Select, Topics};
-
+use crate::{
+    finder::{
+        budget::SearchBudget,
+        searcher::{Acquire, BackstepIter, Searcher},
+        SearchStatus,
+    },
+    messages::{Cache, EventListMessage, FBMessage, TraceMessage},
+    Select, Timestamp, Topics,
+};
+
+pub(crate) use by_frame::SearchByFrameNumber;
 pub(crate) use by_timestamp::SearchByTimestamp;
 pub(crate) use from_end::SearchFromEnd;
+pub(crate) use live::SearchLive;
 
 pub(crate) trait TaskClass {}
 
+/// The frames a search acquires from each topic.
+#[derive(Clone, Copy)]
+pub(crate) enum SoughtFrames<'t> {
+    /// The given number of frames at or after the timestamp.
+    FromTimestamp(Timestamp, usize),
+    /// The frames with the given sorted frame numbers, in the latest run which has the first of them.
+    FrameNumbers(&'t [u32]),
+}
+
+impl SoughtFrames<'_> {
+    /// Returns true if the message is at or after the first frame sought.
+    fn is_reached_by<'a, M: FBMessage<'a>>(&self, msg: &M) -> bool {
+        match *self {
+            SoughtFrames::FromTimestamp(timestamp, _) => msg.timestamp() >= timestamp,
+            SoughtFrames::FrameNumbers(frame_numbers) => {
+                msg.metadata().frame_number >= frame_numbers.first().copied().unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// Decides which messages of a topic are acquired, counting the frames acquired across all its partitions,
+/// so that no more frames are acquired than are sought.
+struct FrameCounter<'t> {
+    sought: SoughtFrames<'t>,
+    /// The timestamps of the frames acquired so far, when a number of frames is sought from a timestamp.
+    timestamps: BTreeSet<Timestamp>,
+}
+
+impl<'t> FrameCounter<'t> {
+    fn new(sought: SoughtFrames<'t>) -> Self {
+        Self {
+            sought,
+            timestamps: Default::default(),
+        }
+    }
+
+    /// Returns whether the message should be acquired, or the partition's acquisition should stop.
+    ///
+    /// # Attributes
+    /// - msg: the message, which is no earlier than the first frame sought.
+    /// - matches: true if the message satisfies the search's filter.
+    fn acquire<'a, M: FBMessage<'a>>(&mut self, msg: &M, matches: bool) -> Acquire {
+        match self.sought {
+            SoughtFrames::FromTimestamp(_, number) => {
+                let timestamp = msg.timestamp();
+                if self.timestamps.len() >= number
+                    && self.timestamps.last().is_none_or(|&last| timestamp > last)
+                {
+                    Acquire::Stop
+                } else if matches
+                    && (self.timestamps.len() < number || self.timestamps.contains(&timestamp))
+                {
+                    self.timestamps.insert(timestamp);
+                    Acquire::Take
+                } else {
+                    Acquire::Skip
+                }
+            }
+            SoughtFrames::FrameNumbers(frame_numbers) => {
+                let frame_number = msg.metadata().frame_number;
+                match (
+                    frame_numbers.first().copied(),
+                    frame_numbers.last().copied(),
+                ) {
+                    // Frame numbers outside the range have passed the last frame sought, or are of a later run.
+                    (Some(first), Some(last)) if (first..=last).contains(&frame_number) => {
+                        if matches && frame_numbers.binary_search(&frame_number).is_ok() {
+                            Acquire::Take
+                        } else {
+                            Acquire::Skip
+                        }
+                    }
+                    _ => Acquire::Stop,
+                }
+            }
+        }
+    }
+}
+
+/// The time to wait for the broker to return a topic's metadata.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(1);
+
 pub(crate) struct SearchTask<'a, C: TaskClass> {
     consumer: StreamConsumer,
     send_status: &'a mpsc::Sender<SearchStatus>,
+    /// Set to `true` when the search should be halted.
+    halt: &'a watch::Receiver<bool>,
     select: &'a Select,
     topics: &'a Topics,
     phantom: PhantomData<C>,
@@ -27,12 +123,14 @@ impl<'a, C: TaskClass> SearchTask<'a, C> {
     pub(crate) fn new(
         consumer: StreamConsumer,
         send_status: &'a mpsc::Sender<SearchStatus>,
+        halt: &'a watch::Receiver<bool>,
         select: &'a Select,
         topics: &'a Topics,
     ) -> Self {
         Self {
             consumer,
             send_status,
+            halt,
             select,
             topics,
             phantom: PhantomData,
@@ -45,6 +143,282 @@ impl<'a, C: TaskClass> SearchTask<'a, C> {
             error!("{e}");
         }
     }
+
+    /// Returns true if the search has been halted by the user.
+    pub(crate) fn is_halted(&self) -> bool {
+        *self.halt.borrow()
+    }
+
+    /// Returns true if the search has been halted by the user, or has exhausted its budget.
+    pub(crate) fn is_stopped(&self, budget: &SearchBudget) -> bool {
+        self.is_halted() || budget.is_exhausted()
+    }
+
+    /// Returns the status to emit once the search is over,
+    /// which explains why the search ended early, if it did.
+    ///
+    /// # Attributes
+    /// - budget: the limits of the search.
+    pub(crate) fn finished_status(&self, budget: &SearchBudget) -> SearchStatus {
+        if self.is_halted() {
+            SearchStatus::Halted
+        } else {
+            budget.exhausted().unwrap_or(SearchStatus::Successful)
+        }
+    }
+
+    /// Fetches the ids of every partition of the given topic from the broker's metadata.
+    ///
+    /// If the metadata cannot be fetched, then only partition `0` is returned.
+    ///
+    /// # Attributes
+    /// - topic: the topic whose partitions are required.
+    #[instrument(skip_all)]
+    pub(crate) fn partitions(&self, topic: &str) -> Vec<i32> {
+        match self.consumer.fetch_metadata(Some(topic), METADATA_TIMEOUT) {
+            Ok(metadata) => metadata
+                .topics()
+                .iter()
+                .filter(|t| t.name() == topic)
+                .flat_map(|t| t.partitions().iter().map(|p| p.id()))
+                .collect(),
+            Err(e) => {
+                error!("{e}");
+                vec![0]
+            }
+        }
+    }
+
+    /// Searches every partition of the trace topic, and then every partition of the event list topic,
+    /// and collects what was acquired into a cache.
+    ///
+    /// The frames acquired are counted across every partition of a topic, and each partition is positioned
+    /// at the first frame sought by its own messages. Event lists are attached to their traces,
+    /// and the search's progress is emitted as it goes.
+    /// # Attributes
+    /// - budget: the limits of the search.
+    /// - sought: the frames to acquire from each topic.
+    /// - filter_trace: a predicate which is true of the traces to acquire.
+    /// - filter_eventlist: a predicate which is true of the event lists to acquire.
+    #[instrument(skip_all)]
+    pub(crate) async fn search_partitions<FT, FE>(
+        &self,
+        budget: &SearchBudget,
+        sought: SoughtFrames<'_>,
+        filter_trace: FT,
+        filter_eventlist: FE,
+    ) -> Cache
+    where
+        FT: Fn(&TraceMessage) -> bool,
+        FE: Fn(&EventListMessage) -> bool,
+    {
+        let mut cache = Cache::default();
+
+        // Find Digitiser Traces
+        let mut trace_results = Vec::new();
+        let mut counter = FrameCounter::new(sought);
+        for partition in self.partitions(&self.topics.trace_topic) {
+            if self.is_stopped(budget) {
+                break;
+            }
+            let searcher = Searcher::new(
+                &self.consumer,
+                &self.topics.trace_topic,
+                partition,
+                1,
+                self.send_status.clone(),
+                self.halt.clone(),
+                budget.clone(),
+            );
+            let results = self
+                .search_topic(
+                    searcher,
+                    &mut counter,
+                    SearchStatus::TraceSearchInProgress,
+                    &filter_trace,
+                )
+                .await;
+            trace_results.extend(results);
+        }
+        self.emit_status(SearchStatus::TraceSearchFinished).await;
+
+        // Find Digitiser Event Lists
+        let mut eventlist_results = Vec::new();
+        let mut counter = FrameCounter::new(sought);
+        for partition in self.partitions(&self.topics.digitiser_event_topic) {
+            if self.is_stopped(budget) {
+                break;
+            }
+            let searcher = Searcher::new(
+                &self.consumer,
+                &self.topics.digitiser_event_topic,
+                partition,
+                1,
+                self.send_status.clone(),
+                self.halt.clone(),
+                budget.clone(),
+            );
+            let results = self
+                .search_topic(
+                    searcher,
+                    &mut counter,
+                    SearchStatus::EventListSearchInProgress,
+                    &filter_eventlist,
+                )
+                .await;
+            eventlist_results.extend(results);
+        }
+        self.emit_status(SearchStatus::EventListSearchFinished)
+            .await;
+
+        for trace in trace_results.iter() {
+            cache.push_trace(&trace.get_unpacked_message().expect(""), trace.source());
+        }
+
+        for eventlist in eventlist_results.iter() {
+            cache.push_events(&eventlist.get_unpacked_message().expect(""));
+        }
+        cache.attach_event_lists_to_trace(self.select.pairing.tolerance());
+        cache
+    }
+
+    /// Positions the searcher at the first message at or after the first frame sought,
+    /// and acquires messages from there, as decided by the counter.
+    ///
+    /// # Attributes
+    /// - searcher: the searcher of the partition.
+    /// - counter: decides which messages are acquired, and counts the frames acquired from the topic.
+    /// - emit: creates the status to emit after each pass of positioning the searcher.
+    /// - filter: a predicate which is true of the messages to acquire.
+    /// # Return
+    /// The messages acquired.
+    #[instrument(skip_all)]
+    async fn search_topic<'b, M, E, A>(
+        &self,
+        searcher: Searcher<'b, M, StreamConsumer>,
+        counter: &mut FrameCounter<'_>,
+        emit: E,
+        filter: A,
+    ) -> Vec<M>
+    where
+        E: Fn(u32) -> SearchStatus,
+        M: FBMessage<'b>,
+        A: Fn(&M) -> bool,
+    {
+        self.emit_status(emit(0)).await;
+
+        let sought = counter.sought;
+        let searcher = match sought {
+            SoughtFrames::FromTimestamp(timestamp, _) => {
+                self.position_at_timestamp(searcher, timestamp, &emit).await
+            }
+            SoughtFrames::FrameNumbers(frame_numbers) => {
+                let frame_number = frame_numbers.first().copied().unwrap_or_default();
+                self.position_at_frame_number(searcher, frame_number, &emit)
+                    .await
+            }
+        };
+
+        self.emit_status(emit(self.select.step.num_step_passes))
+            .await;
+
+        searcher
+            .iter_forward()
+            .move_until_message(|msg| sought.is_reached_by(msg))
+            .await
+            .acquire_until(|msg| counter.acquire(msg, filter(msg)))
+            .await
+            .collect()
+            .into()
+    }
+
+    /// Positions the searcher just before the first message at or after the timestamp.
+    ///
+    /// The broker's time index is used if it is available, otherwise the searcher steps backwards.
+    async fn position_at_timestamp<'b, M, E>(
+        &self,
+        searcher: Searcher<'b, M, StreamConsumer>,
+        timestamp: Timestamp,
+        emit: &E,
+    ) -> Searcher<'b, M, StreamConsumer>
+    where
+        E: Fn(u32) -> SearchStatus,
+        M: FBMessage<'b>,
+    {
+        let mut iter = searcher.iter_time_index();
+        iter.query_time_index(timestamp);
+
+        if iter.has_time_index() {
+            iter.bisect_until_time(|t| t >= timestamp).await;
+            iter.collect()
+        } else {
+            // The broker has no time index for this partition, so fall back to stepping backwards.
+            self.backstep_passes(iter.collect().iter_backstep(), emit, |msg: &M| {
+                msg.timestamp() > timestamp
+            })
+            .await
+        }
+    }
+
+    /// Positions the searcher just before the first message at or after the frame number,
+    /// in the latest run which has it.
+    ///
+    /// As frame numbers restart with each run, the searcher steps backwards from the end of the partition.
+    async fn position_at_frame_number<'b, M, E>(
+        &self,
+        searcher: Searcher<'b, M, StreamConsumer>,
+        frame_number: u32,
+        emit: &E,
+    ) -> Searcher<'b, M, StreamConsumer>
+    where
+        E: Fn(u32) -> SearchStatus,
+        M: FBMessage<'b>,
+    {
+        let steps = &self.select.step;
+        let mut iter = searcher.iter_backstep();
+        // If the latest run has not yet reached the frame number, it is in an earlier run,
+        // so step back past the latest run first, in the largest steps.
+        iter.step_size(
+            steps.min_step_size
+                * steps
+                    .step_mul_coef
+                    .pow(steps.num_step_passes.saturating_sub(1)),
+        )
+        .backstep_past(|msg| msg.metadata().frame_number < frame_number)
+        .await;
+        self.backstep_passes(iter, emit, |msg: &M| {
+            msg.metadata().frame_number >= frame_number
+        })
+        .await
+    }
+
+    /// Steps the searcher backwards in passes of decreasing step size, as set by [Select::step],
+    /// to the earliest message satisfying the predicate before one which does not.
+    ///
+    /// # Attributes
+    /// - iter: the backstep iterator of the searcher.
+    /// - emit: creates the status to emit before each pass.
+    /// - f: a predicate taking a message, it should return true when the message is later than the target.
+    async fn backstep_passes<'b, M, E, F>(
+        &self,
+        mut iter: BackstepIter<'b, M, StreamConsumer>,
+        emit: &E,
+        f: F,
+    ) -> Searcher<'b, M, StreamConsumer>
+    where
+        E: Fn(u32) -> SearchStatus,
+        M: FBMessage<'b>,
+        F: Fn(&M) -> bool,
+    {
+        let steps = &self.select.step;
+        for step in 0..steps.num_step_passes {
+            self.emit_status(emit(step)).await;
+            let sz =
+                steps.min_step_size * steps.step_mul_coef.pow(steps.num_step_passes - 1 - step);
+            iter.step_size(sz).backstep_until(&f).await;
+        }
+        iter.collect()
+    }
     /*
     #[instrument(skip_all)]
     async fn search_topic_by_timestamp<M, E, A>(
@@ -1,3 +1,10 @@
+use supermusr_common::Time;
+
+use crate::{
+    cli_structs::UserBounds,
+    messages::{Pipelines, Trace, UnitConversion},
+};
+
 #[derive(Default, Clone)]
 pub(crate) struct Pair<D: Default> {
     pub(crate) time: D,
@@ -11,6 +18,9 @@ pub(crate) struct Bound {
 }
 
 impl Bound {
+    /// [Self::fit] is given this fraction of the range of the data as its margin, wherever an axis is fitted afresh.
+    pub(crate) const FIT_MARGIN: f64 = 0.05;
+
     pub(crate) fn from<'a, D: Default + Ord + Into<f64>, I: Iterator<Item = D> + Clone>(
         buffer: f64,
         data: I,
@@ -20,6 +30,37 @@ impl Bound {
         Bound { min, max }
     }
 
+    /// Creates a bound which fits the data, leaving the given fraction of its range free at either end.
+    ///
+    /// Returns [None] if there is no data.
+    pub(crate) fn fit<I: Iterator<Item = f64>>(margin: f64, data: I) -> Option<Bound> {
+        let (min, max) = data.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+        if min > max {
+            return None;
+        }
+        // Data of a single value is given a range of one unit.
+        let margin = if min < max { (max - min) * margin } else { 0.5 };
+        Some(Bound {
+            min: min - margin,
+            max: max + margin,
+        })
+    }
+
+    /// Replaces each limit with the given one, where set, unless the range would then be empty.
+    fn amend(self, min: Option<f64>, max: Option<f64>) -> Self {
+        let amended = Bound {
+            min: min.unwrap_or(self.min),
+            max: max.unwrap_or(self.max),
+        };
+        if amended.min < amended.max {
+            amended
+        } else {
+            self
+        }
+    }
+
     fn mid_point(&self) -> f64 {
         (self.max + self.min) / 2.0
     }
@@ -39,6 +80,95 @@ impl Bound {
 pub(crate) type Bounds = Pair<Bound>;
 
 impl Bounds {
+    /// Creates bounds which fit the whole of the trace.
+    pub(crate) fn from_trace(trace: &Trace) -> Self {
+        Self::from_traces(&[trace])
+    }
+
+    /// Creates bounds which fit the whole of every trace.
+    pub(crate) fn from_traces(traces: &[&Trace]) -> Self {
+        let len = traces
+            .iter()
+            .map(|trace| trace.len())
+            .max()
+            .unwrap_or_default();
+        Self {
+            time: Bound::from(1.0, [0, len as Time].into_iter()),
+            intensity: Bound::from(1.0, traces.iter().flat_map(|trace| trace.iter().copied())),
+        }
+    }
+
+    /// Widens the intensity bound to take in the results of the pipelines on every trace.
+    ///
+    /// Results which are differences of intensities are taken in as the raw intensities drawn at the same height,
+    /// once the graph is converted into physical units.
+    pub(crate) fn include_processed(
+        mut self,
+        traces: &[&Trace],
+        processing: &Pipelines,
+        conversion: Option<&UnitConversion>,
+    ) -> Self {
+        for pipeline in processing.pipelines() {
+            let relative_conversion = conversion.filter(|_| pipeline.is_relative());
+            for value in traces.iter().flat_map(|trace| pipeline.apply(trace)) {
+                let value = match relative_conversion {
+                    Some(conversion) => {
+                        conversion.raw_intensity(conversion.intensity_difference(value))
+                    }
+                    None => value,
+                };
+                self.intensity.min = self.intensity.min.min(value);
+                self.intensity.max = self.intensity.max.max(value);
+            }
+        }
+        self
+    }
+
+    /// Replaces each limit with the user's, wherever the user has set one.
+    ///
+    /// If the range of an axis would then be empty, as a user's minimum is at or above its maximum,
+    /// the axis keeps the limits it had.
+    pub(crate) fn amend_with_user_bounds(self, user_bounds: &UserBounds) -> Self {
+        Self {
+            time: self.time.amend(
+                user_bounds.time_min.map(Into::into),
+                user_bounds.time_max.map(Into::into),
+            ),
+            intensity: self.intensity.amend(
+                user_bounds.intensity_min.map(Into::into),
+                user_bounds.intensity_max.map(Into::into),
+            ),
+        }
+    }
+
+    /// Converts bounds of sample indices and raw intensities into physical units.
+    pub(crate) fn convert(&self, conversion: &UnitConversion) -> Self {
+        Self {
+            time: Bound {
+                min: conversion.time(self.time.min),
+                max: conversion.time(self.time.max),
+            },
+            intensity: Bound {
+                min: conversion.intensity(self.intensity.min),
+                max: conversion.intensity(self.intensity.max),
+            },
+        }
+    }
+
+    /// Converts bounds in physical units back into sample indices and raw intensities, the inverse of [Self::convert].
+    pub(crate) fn unconvert(&self, conversion: &UnitConversion) -> Self {
+        Self {
+            time: Bound {
+                min: conversion.raw_time(self.time.min),
+                max: conversion.raw_time(self.time.max),
+            },
+            intensity: Bound {
+                min: conversion.raw_intensity(self.intensity.min),
+                max: conversion.raw_intensity(self.intensity.max),
+            },
+        }
+    }
+
     pub(crate) fn mid_point(&self) -> Point {
         Point {
             time: self.time.mid_point(),
@@ -46,10 +176,12 @@ impl Bounds {
         }
     }
 
-    pub(crate) fn transform(&self, zoom_factor: f64, delta: &Point) -> Self {
+    pub(crate) fn transform(&self, zoom_factor: &Point, delta: &Point) -> Self {
         Self {
-            time: self.time.transform(zoom_factor, delta.time),
-            intensity: self.intensity.transform(zoom_factor, delta.intensity),
+            time: self.time.transform(zoom_factor.time, delta.time),
+            intensity: self
+                .intensity
+                .transform(zoom_factor.intensity, delta.intensity),
         }
     }
 
@@ -68,3 +200,66 @@ impl Into<(f64, f64)> for Point {
         (self.time, self.intensity)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn amend_with_user_bounds() {
+        let bounds = Bounds {
+            time: Bound {
+                min: 0.0,
+                max: 100.0,
+            },
+            intensity: Bound {
+                min: 0.0,
+                max: 100.0,
+            },
+        };
+        let amended = bounds.clone().amend_with_user_bounds(&UserBounds {
+            time_min: Some(10),
+            time_max: None,
+            intensity_min: None,
+            intensity_max: Some(50),
+        });
+        assert_eq!((amended.time.min, amended.time.max), (10.0, 100.0));
+        assert_eq!((amended.intensity.min, amended.intensity.max), (0.0, 50.0));
+
+        // A minimum above the maximum would leave an empty range, so the limits are kept.
+        let amended = bounds.amend_with_user_bounds(&UserBounds {
+            time_min: Some(200),
+            time_max: None,
+            intensity_min: Some(60),
+            intensity_max: Some(50),
+        });
+        assert_eq!((amended.time.min, amended.time.max), (0.0, 100.0));
+        assert_eq!((amended.intensity.min, amended.intensity.max), (0.0, 100.0));
+    }
+
+    #[test]
+    fn convert_round_trip() {
+        let conversion = UnitConversion::new(1_000_000_000, 0.122, -500.0).unwrap();
+        let bounds = Bounds {
+            time: Bound {
+                min: 10.0,
+                max: 2000.0,
+            },
+            intensity: Bound {
+                min: 100.0,
+                max: 4000.0,
+            },
+        };
+        let converted = bounds.convert(&conversion);
+        assert!(converted.time.min < converted.time.max);
+        assert!(converted.intensity.min < converted.intensity.max);
+
+        let unconverted = converted.unconvert(&conversion);
+        assert_approx_eq!(unconverted.time.min, bounds.time.min);
+        assert_approx_eq!(unconverted.time.max, bounds.time.max);
+        assert_approx_eq!(unconverted.intensity.min, bounds.intensity.min);
+        assert_approx_eq!(unconverted.intensity.max, bounds.intensity.max);
+    }
+}
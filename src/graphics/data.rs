@@ -0,0 +1,137 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+use supermusr_common::Channel;
+use tracing::instrument;
+
+use crate::{
+    graphics::FileFormat,
+    messages::{DigitiserMetadata, DigitiserTrace, Event, Pipelines, ProcessedTrace, Trace},
+};
+
+/// The result of a processing pipeline on a trace, as written to JSON.
+#[derive(Serialize)]
+struct ProcessedData {
+    pipeline: String,
+    trace: ProcessedTrace,
+}
+
+/// The numeric data of one channel, as written to JSON.
+#[derive(Serialize)]
+struct ChannelData<'a> {
+    channel: Channel,
+    trace: &'a Trace,
+    events: &'a [Event],
+    processed: Vec<ProcessedData>,
+}
+
+/// The numeric data of a digitiser trace, as written to JSON.
+#[derive(Serialize)]
+struct TraceData<'a> {
+    metadata: &'a DigitiserMetadata,
+    channels: Vec<ChannelData<'a>>,
+}
+
+/// Saves the numeric data of traces and their event lists, for analysis elsewhere.
+pub(crate) struct DataExporter;
+
+impl DataExporter {
+    /// Saves the given channels of the trace, their event lists and processed traces, in the given format.
+    ///
+    /// # Attributes
+    /// - format: either [FileFormat::Csv] or [FileFormat::Json].
+    /// - metadata: the metadata of the trace, written as a header.
+    /// - trace: the trace to save.
+    /// - channels: the channels of the trace to save.
+    /// - processing: the pipelines whose results are saved alongside each channel.
+    /// - path: the file to save to.
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn save(
+        format: FileFormat,
+        metadata: &DigitiserMetadata,
+        trace: &DigitiserTrace,
+        channels: Vec<Channel>,
+        processing: &Pipelines,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        let data = TraceData {
+            metadata,
+            channels: channels
+                .into_iter()
+                .map(|channel| ChannelData {
+                    channel,
+                    trace: &trace.traces[&channel],
+                    events: trace
+                        .events
+                        .as_ref()
+                        .and_then(|events| events.get(&channel))
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                    processed: processing
+                        .pipelines()
+                        .iter()
+                        .map(|pipeline| ProcessedData {
+                            pipeline: pipeline.to_string(),
+                            trace: pipeline.apply(&trace.traces[&channel]),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        match format {
+            FileFormat::Csv => Self::write_csv(&mut writer, &data)?,
+            FileFormat::Json => serde_json::to_writer_pretty(&mut writer, &data)?,
+            _ => anyhow::bail!("{format} is not a data format"),
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the metadata as `#` comment lines, followed by one row per trace sample, per event and per processed sample.
+    ///
+    /// The `kind` column is `trace`, `event`, or the pipeline of a processed trace, so they can be separated after loading.
+    fn write_csv<W: Write>(writer: &mut W, data: &TraceData) -> anyhow::Result<()> {
+        let metadata = data.metadata;
+        writeln!(writer, "# timestamp: {}", metadata.timestamp.to_rfc3339())?;
+        writeln!(writer, "# digitiser_id: {}", metadata.id)?;
+        writeln!(writer, "# frame_number: {}", metadata.frame_number)?;
+        writeln!(writer, "# period_number: {}", metadata.period_number)?;
+        writeln!(
+            writer,
+            "# protons_per_pulse: {}",
+            metadata.protons_per_pulse
+        )?;
+        writeln!(writer, "# running: {}", metadata.running)?;
+        writeln!(writer, "# veto_flags: {}", metadata.veto_flags)?;
+        writeln!(writer, "kind,channel,time,intensity")?;
+
+        for channel in &data.channels {
+            for (time, intensity) in channel.trace.iter().enumerate() {
+                writeln!(writer, "trace,{},{time},{intensity}", channel.channel)?;
+            }
+            for event in channel.events {
+                writeln!(
+                    writer,
+                    "event,{},{},{}",
+                    channel.channel, event.time, event.intensity
+                )?;
+            }
+            for processed in &channel.processed {
+                for (time, intensity) in processed.trace.iter().enumerate() {
+                    writeln!(
+                        writer,
+                        "{},{},{time},{intensity}",
+                        processed.pipeline, channel.channel
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 mod bounds;
-mod svg;
+mod data;
+#[cfg(feature = "nexus")]
+mod nexus;
+mod saver;
 
 use std::{
     fs::create_dir_all,
@@ -9,28 +12,83 @@ use std::{
 use strum::{Display, EnumIter, EnumString};
 use supermusr_common::Channel;
 
-use crate::messages::{DigitiserMetadata, DigitiserTrace};
+use crate::{
+    cli_structs::{IntensityScale, Units, UserBounds},
+    messages::{Cache, DigitiserMetadata, DigitiserTrace, Pipelines, UnitConversion},
+};
 
 pub(crate) use bounds::{Bound, Bounds, Point};
-pub(crate) use svg::SvgSaver;
+pub(crate) use data::DataExporter;
+#[cfg(feature = "nexus")]
+pub(crate) use nexus::NexusExporter;
+pub(crate) use saver::PlottersSaver;
+
+/// How several channels are arranged in a saved graph.
+#[derive(Clone, Copy, EnumString, Display, EnumIter)]
+pub(crate) enum GraphLayout {
+    /// Every channel is drawn on one chart, each in a distinct colour.
+    #[strum(to_string = "overlay")]
+    Overlay,
+    /// Each channel is drawn on its own chart, all sharing the same axes.
+    #[strum(to_string = "grid")]
+    Grid,
+}
 
-#[derive(Clone, EnumString, Display, EnumIter)]
+#[derive(Clone, Copy, EnumString, Display, EnumIter)]
 pub(crate) enum FileFormat {
     #[strum(to_string = "svg")]
     Svg,
+    #[strum(to_string = "png")]
+    Png,
+    #[strum(to_string = "csv")]
+    Csv,
+    #[strum(to_string = "json")]
+    Json,
+    /// Only available with the `nexus` feature.
+    #[cfg(feature = "nexus")]
+    #[strum(to_string = "nxs")]
+    Nexus,
 }
 
 impl FileFormat {
+    /// Returns true if the format holds the numeric data of every channel of a digitiser trace in one file.
+    pub(crate) fn is_data(&self) -> bool {
+        matches!(self, Self::Csv | Self::Json)
+    }
+
+    /// Returns true if the format is saved by a [GraphSaver], rather than an exporter.
+    pub(crate) fn is_image(&self) -> bool {
+        matches!(self, Self::Svg | Self::Png)
+    }
+
     pub(crate) fn build_path<'a>(
         self,
         path: &'a Path,
         metadata: &DigitiserMetadata,
         channel: Channel,
+    ) -> anyhow::Result<PathBuf> {
+        self.build_path_with_name(path, metadata, channel.to_string())
+    }
+
+    /// Builds the path of a file holding several channels of the digitiser's trace.
+    pub(crate) fn build_digitiser_path<'a>(
+        self,
+        path: &'a Path,
+        metadata: &DigitiserMetadata,
+    ) -> anyhow::Result<PathBuf> {
+        self.build_path_with_name(path, metadata, format!("digitiser_{}", metadata.id))
+    }
+
+    fn build_path_with_name<'a>(
+        self,
+        path: &'a Path,
+        metadata: &DigitiserMetadata,
+        name: String,
     ) -> anyhow::Result<PathBuf> {
         let mut path_buf = path.to_owned();
         path_buf.push(metadata.timestamp.to_rfc3339());
         create_dir_all(&path_buf)?;
-        path_buf.push(channel.to_string());
+        path_buf.push(name);
 
         if path_buf.set_extension(self.to_string()) {
             Ok(path_buf)
@@ -44,12 +102,123 @@ impl FileFormat {
     }
 }
 
+/// How a graph of a trace is drawn.
+#[derive(Clone)]
+pub(crate) struct RenderOptions {
+    /// The layout of the graph, if it has several channels.
+    pub(crate) layout: GraphLayout,
+    /// The size of the image, in pixels.
+    pub(crate) size: (u32, u32),
+    /// The bounds of the graph, in sample indices and raw intensities.
+    pub(crate) bounds: Bounds,
+    /// The conversion of the graph into physical units, if any.
+    pub(crate) conversion: Option<UnitConversion>,
+    /// The scale of the intensity axis.
+    pub(crate) scale: IntensityScale,
+    /// The pipelines whose results are graphed, or saved, alongside each channel.
+    pub(crate) processing: Pipelines,
+}
+
 pub(crate) trait GraphSaver: Default {
-    fn save_as_svg(
+    /// Saves a graph of the given channels of the trace, in the given format,
+    /// with the results of the processing pipelines graphed alongside each channel.
+    fn save(
+        format: FileFormat,
         trace: &DigitiserTrace,
         channels: Vec<Channel>,
         path: PathBuf,
-        size: (u32, u32),
-        bounds: Bounds,
+        options: &RenderOptions,
     ) -> Result<(), anyhow::Error>;
 }
+
+/// Saves channels of the trace to a single file in the `path` directory.
+///
+/// A single channel is laid out by [FileFormat::build_path], and several by [FileFormat::build_digitiser_path].
+/// Image formats are saved by `G`, NeXus files by the [NexusExporter], and other data formats by the [DataExporter].
+/// # Attributes
+/// - format: the format to save in.
+/// - path: the directory to save to.
+/// - metadata: the metadata of the trace.
+/// - trace: the trace to save.
+/// - channels: the channels of the trace to save.
+/// - options: how the graph is drawn, if the format is an image format, and the pipelines whose results
+///   are saved alongside each channel in any format.
+pub(crate) fn save_channels<G: GraphSaver>(
+    format: FileFormat,
+    path: &Path,
+    metadata: &DigitiserMetadata,
+    trace: &DigitiserTrace,
+    channels: Vec<Channel>,
+    options: &RenderOptions,
+) -> anyhow::Result<()> {
+    let path_buf = match channels.as_slice() {
+        &[channel] => format.build_path(path, metadata, channel)?,
+        _ => format.build_digitiser_path(path, metadata)?,
+    };
+    if format.is_image() {
+        return G::save(format, trace, channels, path_buf, options);
+    }
+    #[cfg(feature = "nexus")]
+    if let FileFormat::Nexus = format {
+        return NexusExporter::save(metadata, trace, channels, &options.processing, &path_buf);
+    }
+    DataExporter::save(
+        format,
+        metadata,
+        trace,
+        channels,
+        &options.processing,
+        &path_buf,
+    )
+}
+
+/// Saves every channel of every trace in the cache, each to its own file, using [save_channels].
+///
+/// CSV and JSON files instead hold every channel of a digitiser trace, so one is saved per trace.
+/// Each graph is bounded to fit its whole trace and processed traces, other than where `user_bounds` are set,
+/// and converted into physical units by `units`, in place of the bounds and conversion of `options`.
+/// NeXus files instead hold the whole cache, so a single file is saved in the `path` directory,
+/// named after the timestamp of the earliest trace.
+/// # Return
+/// The number of files saved.
+pub(crate) fn save_cache<G: GraphSaver>(
+    format: FileFormat,
+    path: &Path,
+    cache: &Cache,
+    options: &RenderOptions,
+    user_bounds: &UserBounds,
+    units: &Units,
+) -> anyhow::Result<usize> {
+    #[cfg(feature = "nexus")]
+    if let FileFormat::Nexus = format {
+        let Some((metadata, _)) = cache.iter_traces().next() else {
+            return Ok(0);
+        };
+        create_dir_all(path)?;
+        let path_buf = path.join(format!("{}.{format}", metadata.timestamp.to_rfc3339()));
+        NexusExporter::save_cache(cache, &options.processing, &path_buf)?;
+        return Ok(1);
+    }
+
+    let mut num_saved = 0;
+    for (metadata, trace) in cache.iter_traces() {
+        if format.is_data() {
+            save_channels::<G>(format, path, metadata, trace, trace.channels(), options)?;
+            num_saved += 1;
+            continue;
+        }
+        let conversion = units.conversion(metadata.id, trace);
+        for (&channel, channel_trace) in trace.traces.iter() {
+            let options = RenderOptions {
+                bounds: Bounds::from_trace(channel_trace)
+                    .include_processed(&[channel_trace], &options.processing, conversion.as_ref())
+                    .amend_with_user_bounds(user_bounds),
+                conversion,
+                ..options.clone()
+            };
+            save_channels::<G>(format, path, metadata, trace, vec![channel], &options)?;
+            num_saved += 1;
+        }
+    }
+    Ok(num_saved)
+}
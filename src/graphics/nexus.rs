@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use hdf5::{types::VarLenUnicode, Group, H5Type, Location};
+use supermusr_common::{Channel, Intensity, Time};
+use tracing::instrument;
+
+use crate::messages::{Cache, DigitiserMetadata, DigitiserTrace, Pipelines};
+
+/// Saves traces and their event lists to a NeXus (HDF5) file.
+///
+/// Each trace is written to its own `NXentry` group, whose attributes hold its [DigitiserMetadata],
+/// and which contains one `NXdata` group per channel, holding the datasets
+/// `trace`, `event_time_offset` and `pulse_height`, and `processed_0`, `processed_1`, etc.
+/// holding the result of each processing pipeline, whose `pipeline` attribute names it.
+///
+/// The event datasets are named as in the `NXevent_data` group of the nexus-writer's files.
+/// Otherwise the layout differs from the nexus-writer's, as its files hold the event lists of a whole run
+/// in a single `raw_data_1` entry, without traces, whereas these hold a few traces, each with its events.
+/// Event times are also in samples, rather than nanoseconds.
+pub(crate) struct NexusExporter;
+
+impl NexusExporter {
+    /// Saves every channel of every trace in the cache to a single file.
+    ///
+    /// # Attributes
+    /// - cache: the traces to save.
+    /// - processing: the pipelines whose results are saved alongside each channel.
+    /// - path: the file to save to.
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn save_cache(
+        cache: &Cache,
+        processing: &Pipelines,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        let file = Self::create(path)?;
+        for (metadata, trace) in cache.iter_traces() {
+            let channels = trace.traces.keys().copied().collect();
+            Self::write_trace(&file, metadata, trace, channels, processing)?;
+        }
+        file.close()?;
+        Ok(())
+    }
+
+    /// Saves the given channels of the trace to a file.
+    ///
+    /// # Attributes
+    /// - metadata: the metadata of the trace.
+    /// - trace: the trace to save.
+    /// - channels: the channels of the trace to save.
+    /// - processing: the pipelines whose results are saved alongside each channel.
+    /// - path: the file to save to.
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn save(
+        metadata: &DigitiserMetadata,
+        trace: &DigitiserTrace,
+        channels: Vec<Channel>,
+        processing: &Pipelines,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        let file = Self::create(path)?;
+        Self::write_trace(&file, metadata, trace, channels, processing)?;
+        file.close()?;
+        Ok(())
+    }
+
+    fn create(path: &Path) -> anyhow::Result<hdf5::File> {
+        let file = hdf5::File::create(path)?;
+        Self::write_attr(&file, "NX_class", &"NXroot".parse::<VarLenUnicode>()?)?;
+        Ok(file)
+    }
+
+    /// Writes the trace to a new group of the file, named after its timestamp and digitiser id.
+    fn write_trace(
+        file: &hdf5::File,
+        metadata: &DigitiserMetadata,
+        trace: &DigitiserTrace,
+        mut channels: Vec<Channel>,
+        processing: &Pipelines,
+    ) -> anyhow::Result<()> {
+        let entry = file.create_group(&format!(
+            "{}_digitiser_{}",
+            metadata.timestamp.to_rfc3339(),
+            metadata.id
+        ))?;
+        Self::write_attr(&entry, "NX_class", &"NXentry".parse::<VarLenUnicode>()?)?;
+        Self::write_metadata(&entry, metadata)?;
+
+        channels.sort();
+        for channel in channels {
+            let group = entry.create_group(&format!("channel_{channel}"))?;
+            Self::write_attr(&group, "NX_class", &"NXdata".parse::<VarLenUnicode>()?)?;
+            Self::write_attr(&group, "channel", &channel)?;
+
+            group
+                .new_dataset_builder()
+                .with_data(&trace.traces[&channel])
+                .create("trace")?;
+
+            let events = trace
+                .events
+                .as_ref()
+                .and_then(|events| events.get(&channel))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let (times, intensities): (Vec<Time>, Vec<Intensity>) = events
+                .iter()
+                .map(|event| (event.time, event.intensity))
+                .unzip();
+            group
+                .new_dataset_builder()
+                .with_data(&times)
+                .create("event_time_offset")?;
+            group
+                .new_dataset_builder()
+                .with_data(&intensities)
+                .create("pulse_height")?;
+
+            for (index, pipeline) in processing.pipelines().iter().enumerate() {
+                let dataset = group
+                    .new_dataset_builder()
+                    .with_data(&pipeline.apply(&trace.traces[&channel]))
+                    .create(format!("processed_{index}").as_str())?;
+                Self::write_attr(
+                    &dataset,
+                    "pipeline",
+                    &pipeline.to_string().parse::<VarLenUnicode>()?,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_metadata(group: &Group, metadata: &DigitiserMetadata) -> anyhow::Result<()> {
+        Self::write_attr(
+            group,
+            "timestamp",
+            &metadata.timestamp.to_rfc3339().parse::<VarLenUnicode>()?,
+        )?;
+        Self::write_attr(group, "digitiser_id", &metadata.id)?;
+        Self::write_attr(group, "frame_number", &metadata.frame_number)?;
+        Self::write_attr(group, "period_number", &metadata.period_number)?;
+        Self::write_attr(group, "protons_per_pulse", &metadata.protons_per_pulse)?;
+        Self::write_attr(group, "running", &metadata.running)?;
+        Self::write_attr(group, "veto_flags", &metadata.veto_flags)?;
+        Ok(())
+    }
+
+    fn write_attr<T: H5Type>(location: &Location, name: &str, value: &T) -> anyhow::Result<()> {
+        location.new_attr::<T>().create(name)?.write_scalar(value)?;
+        Ok(())
+    }
+}
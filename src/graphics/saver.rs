@@ -0,0 +1,492 @@
+use std::{path::PathBuf, sync::Once};
+
+use plotters::{
+    chart::{ChartBuilder, ChartContext},
+    coord::{types::RangedCoordf64, Shift},
+    prelude::{
+        BitMapBackend, Cartesian2d, Circle, DrawingArea, DrawingBackend, IntoDrawingArea,
+        PathElement, SVGBackend,
+    },
+    series::{LineSeries, PointSeries},
+    style::{
+        register_font, Color, FontStyle, IntoFont, Palette, Palette99, RGBAColor, ShapeStyle, WHITE,
+    },
+};
+use supermusr_common::Channel;
+use tracing::instrument;
+
+use crate::{
+    cli_structs::IntensityScale,
+    graphics::{Bound, Bounds, FileFormat, GraphLayout, RenderOptions},
+    messages::{
+        AxisLabel, DigitiserTrace, EventList, Pipeline, Pipelines, PointConversion, Trace,
+        UnitConversion,
+    },
+    GraphSaver,
+};
+
+/// The font of the text of saved graphs, bundled so that bitmaps have text without depending on system fonts.
+const FONT: &[u8] = include_bytes!("../../assets/DejaVuSans.ttf");
+
+type MyChartContext<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+trait MyBuilder<'a, DB: DrawingBackend>: Sized {
+    fn build_trace_graph(
+        root: &'a DrawingArea<DB, Shift>,
+        caption: &str,
+        bounds: Bounds,
+        labels: &(AxisLabel, AxisLabel),
+    ) -> anyhow::Result<Self>;
+    fn draw_eventlist_to_chart(
+        &mut self,
+        eventlist: &EventList,
+        label: &str,
+        colour: RGBAColor,
+        point_conversion: &PointConversion,
+    ) -> Result<(), anyhow::Error>;
+    fn draw_trace_to_chart(
+        &mut self,
+        trace: &Trace,
+        label: &str,
+        colour: RGBAColor,
+        point_conversion: &PointConversion,
+    ) -> Result<(), anyhow::Error>;
+    fn draw_processed_to_chart(
+        &mut self,
+        trace: &Trace,
+        pipeline: &Pipeline,
+        colour: RGBAColor,
+        point_conversion: &PointConversion,
+    ) -> Result<(), anyhow::Error>;
+}
+
+/// What is common to every chart of a saved graph.
+struct Graph<'a> {
+    trace: &'a DigitiserTrace,
+    /// The bounds of the graph, in the units of the graph.
+    bounds: Bounds,
+    labels: (AxisLabel, AxisLabel),
+    conversion: Option<&'a UnitConversion>,
+    /// The scale of the intensity axis.
+    scale: &'a IntensityScale,
+    /// The pipelines whose results are drawn alongside each channel.
+    processing: &'a Pipelines,
+}
+
+impl Graph<'_> {
+    /// Returns the conversion of the points of the given channel into the units and scale of the graph.
+    fn point_conversion(&self, channel: Channel) -> PointConversion {
+        PointConversion::new(
+            self.conversion,
+            self.scale
+                .transform(&self.trace.traces[&channel], self.conversion),
+        )
+    }
+}
+
+/// Saves graphs using the [plotters] backend which matches the [FileFormat].
+#[derive(Default)]
+pub(crate) struct PlottersSaver {}
+
+impl<'a, DB> MyBuilder<'a, DB> for MyChartContext<'a, DB>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+{
+    #[instrument(skip_all, level = "debug")]
+    fn build_trace_graph(
+        root: &'a DrawingArea<DB, Shift>,
+        caption: &str,
+        bounds: Bounds,
+        (time_label, intensity_label): &(AxisLabel, AxisLabel),
+    ) -> anyhow::Result<MyChartContext<'a, DB>> {
+        // Keep the caption in proportion, when the chart is one of a grid.
+        let caption_size = (root.dim_in_pixel().1 as f64 / 12.0).min(50.0);
+        let mut chart = ChartBuilder::on(root)
+            .x_label_area_size(35)
+            .y_label_area_size(40)
+            //.right_y_label_area_size(40)
+            .margin(5)
+            .caption(caption, ("sans-serif", caption_size).into_font())
+            .build_cartesian_2d(
+                bounds.time.min..bounds.time.max,
+                bounds.intensity.min..bounds.intensity.max,
+            )?;
+        //.set_secondary_coord(0f32..10f32, -1.0f32..1.0f32);
+
+        let time_formatter = |x: &f64| time_label.format(*x, 1);
+        let intensity_formatter = |y: &f64| intensity_label.format(*y, 1);
+        let raw_formatter = |y: &f64| format!("{:e}", y);
+
+        let mut mesh = chart.configure_mesh();
+        mesh.disable_x_mesh().disable_y_mesh();
+        if !intensity_label.is_raw() {
+            mesh.x_desc(time_label.title())
+                .y_desc(intensity_label.title())
+                .x_label_formatter(&time_formatter)
+                .y_label_formatter(&intensity_formatter);
+        } else {
+            mesh.y_label_formatter(&raw_formatter);
+        }
+        mesh.draw()?;
+
+        Ok(chart)
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    fn draw_eventlist_to_chart(
+        &mut self,
+        eventlist: &EventList,
+        label: &str,
+        colour: RGBAColor,
+        point_conversion: &PointConversion,
+    ) -> Result<(), anyhow::Error> {
+        let time_range = self.x_range();
+        let data = eventlist
+            .iter()
+            .map(|el| point_conversion.apply(el.time as f64, el.intensity as f64))
+            .filter(|(time, _)| time_range.contains(time));
+        let ps: PointSeries<_, _, Circle<_, _>, _> =
+            PointSeries::new(data, 4, ShapeStyle::from(&colour));
+        self.draw_series(ps)?
+            .label(label)
+            .legend(move |(x, y)| Circle::new((x, y), 4, colour));
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    fn draw_trace_to_chart(
+        &mut self,
+        trace: &Trace,
+        label: &str,
+        colour: RGBAColor,
+        point_conversion: &PointConversion,
+    ) -> Result<(), anyhow::Error> {
+        // Only the part of the trace within the time bounds is drawn, in case the chart is zoomed in.
+        let time_range = self.x_range();
+        let data = trace
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(x, y)| point_conversion.apply(x as f64, y as f64))
+            .filter(|(x, _)| time_range.contains(x));
+
+        self.draw_series(LineSeries::new(data, &colour))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x - 10, y), (x + 10, y)], colour));
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    fn draw_processed_to_chart(
+        &mut self,
+        trace: &Trace,
+        pipeline: &Pipeline,
+        colour: RGBAColor,
+        point_conversion: &PointConversion,
+    ) -> Result<(), anyhow::Error> {
+        let point_conversion = point_conversion.for_pipeline(pipeline);
+        let time_range = self.x_range();
+        let data = pipeline
+            .apply(trace)
+            .into_iter()
+            .enumerate()
+            .map(|(x, y)| point_conversion.apply(x as f64, y))
+            .filter(|(x, _)| time_range.contains(x));
+
+        self.draw_series(LineSeries::new(data, &colour))?
+            .label(pipeline.to_string())
+            .legend(move |(x, y)| PathElement::new(vec![(x - 10, y), (x + 10, y)], colour));
+        Ok(())
+    }
+}
+
+impl PlottersSaver {
+    /// Draws the given channels of the trace, and their event lists, onto the drawing area as the options give.
+    ///
+    /// Each channel, and its events, are drawn in a distinct colour, as is each of its processed traces.
+    #[instrument(skip_all, level = "debug")]
+    fn draw<DB>(
+        root: DrawingArea<DB, Shift>,
+        trace: &DigitiserTrace,
+        channels: Vec<Channel>,
+        options: &RenderOptions,
+    ) -> Result<(), anyhow::Error>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+
+        let RenderOptions {
+            layout,
+            scale,
+            processing,
+            ..
+        } = options;
+        let conversion = options.conversion.as_ref();
+        let bounds = match conversion {
+            Some(conversion) => options.bounds.convert(conversion),
+            None => options.bounds.clone(),
+        };
+        let labels = AxisLabel::for_graph(conversion, bounds.time.range(), scale);
+        let mut graph = Graph {
+            trace,
+            bounds,
+            labels,
+            conversion,
+            scale,
+            processing,
+        };
+
+        // The intensity axis is fitted to the rescaled data, which the raw bounds say nothing about.
+        if !scale.is_linear() {
+            let time = graph.bounds.time.clone();
+            let intensities = channels
+                .iter()
+                .flat_map(|&channel| {
+                    let point_conversion = graph.point_conversion(channel);
+                    let events = trace
+                        .events
+                        .as_ref()
+                        .and_then(|events| events.get(&channel))
+                        .into_iter()
+                        .flatten()
+                        .map(|event| (event.time as f64, event.intensity as f64));
+                    let processed = processing.pipelines().iter().flat_map(move |pipeline| {
+                        let point_conversion = point_conversion.for_pipeline(pipeline);
+                        pipeline
+                            .apply(&trace.traces[&channel])
+                            .into_iter()
+                            .enumerate()
+                            .map(move |(t, v)| point_conversion.apply(t as f64, v))
+                    });
+                    trace.traces[&channel]
+                        .iter()
+                        .enumerate()
+                        .map(|(t, &v)| (t as f64, v as f64))
+                        .chain(events)
+                        .map(move |(t, v)| point_conversion.apply(t, v))
+                        .chain(processed)
+                })
+                .filter(|(t, _)| time.min <= *t && *t <= time.max)
+                .map(|(_, intensity)| intensity);
+            if let Some(intensity) = Bound::fit(Bound::FIT_MARGIN, intensities) {
+                graph.bounds.intensity = intensity;
+            }
+        }
+
+        let channels: Vec<(usize, Channel)> = channels.into_iter().enumerate().collect();
+        match layout {
+            GraphLayout::Overlay => Self::draw_chart(&root, "Trace", &graph, &channels)?,
+            GraphLayout::Grid => {
+                // As near to square as possible.
+                let cols = (channels.len() as f64).sqrt().ceil().max(1.0) as usize;
+                let rows = channels.len().div_ceil(cols).max(1);
+                for (area, channel) in root.split_evenly((rows, cols)).iter().zip(&channels) {
+                    Self::draw_chart(
+                        area,
+                        &format!("Channel {}", channel.1),
+                        &graph,
+                        std::slice::from_ref(channel),
+                    )?;
+                }
+            }
+        }
+
+        root.present()?;
+        Ok(())
+    }
+
+    /// Draws one chart of the given channels, each paired with the index of its colour.
+    fn draw_chart<DB>(
+        root: &DrawingArea<DB, Shift>,
+        caption: &str,
+        graph: &Graph,
+        channels: &[(usize, Channel)],
+    ) -> Result<(), anyhow::Error>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let mut chart =
+            MyChartContext::build_trace_graph(root, caption, graph.bounds.clone(), &graph.labels)?;
+
+        let trace = graph.trace;
+        // Processed traces take the colours after those of every channel, so they are distinct from all of them.
+        let num_channels = channels
+            .iter()
+            .map(|&(index, _)| index + 1)
+            .max()
+            .unwrap_or(0);
+        for &(index, c) in channels {
+            let colour = Palette99::pick(index).to_rgba();
+            let point_conversion = graph.point_conversion(c);
+            chart.draw_trace_to_chart(
+                &trace.traces[&c],
+                &format!("trace[{c}]"),
+                colour,
+                &point_conversion,
+            )?;
+            if let Some(eventlist) = trace.events.as_ref().and_then(|events| events.get(&c)) {
+                chart.draw_eventlist_to_chart(
+                    eventlist,
+                    &format!("event[{c}]"),
+                    colour,
+                    &point_conversion,
+                )?;
+            }
+            for (k, pipeline) in graph.processing.pipelines().iter().enumerate() {
+                chart.draw_processed_to_chart(
+                    &trace.traces[&c],
+                    pipeline,
+                    Palette99::pick(num_channels * (k + 1) + index).to_rgba(),
+                    &point_conversion,
+                )?;
+            }
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE)
+            .draw()?;
+        Ok(())
+    }
+}
+
+impl GraphSaver for PlottersSaver {
+    fn save(
+        format: FileFormat,
+        trace: &DigitiserTrace,
+        channels: Vec<Channel>,
+        path: PathBuf,
+        options: &RenderOptions,
+    ) -> Result<(), anyhow::Error> {
+        static REGISTER_FONT: Once = Once::new();
+        REGISTER_FONT.call_once(|| register_font("sans-serif", FontStyle::Normal, FONT).expect(""));
+
+        match format {
+            FileFormat::Svg => Self::draw(
+                SVGBackend::new(&path, options.size).into_drawing_area(),
+                trace,
+                channels,
+                options,
+            ),
+            FileFormat::Png => Self::draw(
+                BitMapBackend::new(&path, options.size).into_drawing_area(),
+                trace,
+                channels,
+                options,
+            ),
+            _ => anyhow::bail!("{format} is not an image format"),
+        }
+    }
+}
+/*
+pub(crate) struct BuildGraph<'b,B> where B: Backend<'b> {
+    width: usize,
+    height: usize,
+    x_range: Range<f32>,
+    y_range: Range<f32>,
+    phantom: PhantomData<&'b B>,
+}
+
+impl<'b, B> BuildGraph<'b, B> where B: Backend<'b>, <B::Backend as DrawingBackend>::ErrorType: 'static {
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn new(width: usize, height: usize, x_range: Range<f32>, y_range: Range<f32>) -> Self {
+        Self {
+            width,
+            height,
+            x_range,
+            y_range,
+            phantom: Default::default()
+        }
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn build_path(&self, path: &'b Path, metadata: &DigitiserMetadata, channel: Channel) -> Result<PathBuf, anyhow::Error> {
+        let mut path_buf = path.to_owned();
+        path_buf.push(metadata.timestamp.to_rfc3339());
+        create_dir_all(&path_buf)?;
+        path_buf.push(channel.to_string());
+
+        if path_buf.set_extension(B::EXTENSION) {
+            Ok(path_buf)
+        } else {
+            bail!("Could not set file extension {} to {:?}", B::EXTENSION, path_buf);
+        }
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn save_trace_graph(&self, path: &'b Path, trace: &Trace, eventlist: Option<&EventList>) -> Result<(), anyhow::Error> {
+        info!("Saving to file {path:?}");
+        let root = B::new(path, (self.width as u32, self.height as u32)).into_drawing_area();
+        root.fill(&WHITE)?;
+        self.build_trace_graph(&root, trace, eventlist)?;
+        root.present()?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    pub(crate) fn build_trace_graph<'a>(&'a self, root: &'a DrawingArea<B::Backend, Shift>, trace: &Trace, eventlist: Option<&EventList>) -> Result<(), anyhow::Error>
+    {
+        let mut chart = ChartBuilder::on(root)
+            .x_label_area_size(35)
+            .y_label_area_size(40)
+            //.right_y_label_area_size(40)
+            .margin(5)
+            .caption("Trace", ("sans-serif", 50.0).into_font())
+            .build_cartesian_2d(self.x_range.clone(), self.y_range.clone())?;
+            //.set_secondary_coord(0f32..10f32, -1.0f32..1.0f32);
+
+
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .disable_y_mesh()
+            //.y_desc("Log Scale")
+            .y_label_formatter(&|x| format!("{:e}", x))
+            .draw()?;
+
+        self.draw_trace_to_chart(&mut chart, trace)?;
+        if let Some(eventlist) = eventlist {
+            self.draw_eventlist_to_chart(&mut chart, eventlist)?;
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE)
+            .draw()?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, level = "debug")]
+    fn draw_eventlist_to_chart<'a>(&'a self, chart: &'a mut ChartContext<B::Backend, Cartesian2d<RangedCoordf32, RangedCoordf32>>, eventlist: &EventList) -> Result<(), anyhow::Error>
+    {
+        let data = eventlist.iter().map(|el|(el.time as f32, el.intensity as f32));
+        let ps : PointSeries<_,_,Circle<_,_>,_> = PointSeries::new(data, 4, ShapeStyle::from(&BLACK));
+        chart
+            .draw_series(ps)?
+            .label("y = Event")
+            .legend(|(x, y)| Circle::new((x,y), 4, BLACK));
+        Ok(())
+    }
+    #[instrument(skip_all, level = "debug")]
+    fn draw_trace_to_chart<'a>(&'a self, chart: &'a mut ChartContext<B::Backend, Cartesian2d<RangedCoordf32, RangedCoordf32>>, trace: &Trace) -> Result<(), anyhow::Error>
+    {
+        let data = trace.iter().cloned().enumerate().map(|(x,y)|(x as f32, y as f32));
+        chart
+            .draw_series(LineSeries::new(
+                data,
+                &BLUE,
+            ))?
+            .label("y = Trace")
+            .legend(|(x, y)| PathElement::new(vec![(x - 10, y), (x + 10, y)], BLUE));
+        Ok(())
+    }
+
+    pub(crate) fn build_event_graph(&self, root: &DrawingArea<SVGBackend, Shift>, trace: Trace) -> Result<(), anyhow::Error> {
+
+        Ok(())
+    }
+} */
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{info, instrument, warn};
+
+use crate::{
+    cli_structs::{IntensityScale, OutputToFile, Units, UserBounds},
+    finder::{MessageFinder, SearchMode, SearchResults, SearchStatus, SearchTarget},
+    graphics::{self, Bounds, GraphLayout, GraphSaver, RenderOptions},
+    messages::Pipelines,
+    Select,
+};
+
+/// Performs a search without a terminal, and saves every channel of every trace found.
+///
+/// A SIGINT halts the search, and the traces found before it was halted are still saved.
+/// # Attributes
+/// - message_finder: the engine with which to perform the search.
+/// - select: the search parameters given on the command line.
+/// - bounds: the graph bounds given on the command line.
+/// - units: the conversion of graphs into physical units given on the command line.
+/// - scale: the scale of the intensity axis of graphs given on the command line.
+/// - processing: the pipelines graphed alongside each trace given on the command line.
+/// - output: which search to perform, and where and how to save the files.
+/// # Return
+/// The number of files saved, or an error if there was nothing to save.
+#[instrument(skip_all)]
+pub(crate) async fn output_to_file<M: MessageFinder, G: GraphSaver>(
+    mut message_finder: M,
+    select: &Select,
+    bounds: &UserBounds,
+    units: &Units,
+    scale: &IntensityScale,
+    processing: &Pipelines,
+    output: &OutputToFile,
+) -> anyhow::Result<usize> {
+    if let SearchMode::Live = output.search_mode {
+        anyhow::bail!("A live search only ends when halted, so cannot be output to file.");
+    }
+
+    message_finder.init_search(SearchTarget {
+        mode: output.search_mode,
+        timestamp: select.timestamp,
+        channels: select.channels.values().to_vec(),
+        digitiser_ids: select.digitiser_ids.values().to_vec(),
+        frame_numbers: select.frame_numbers.values().to_vec(),
+        number: output.number,
+        timeout: Duration::from_secs(select.limits.search_timeout),
+        max_messages_scanned: select.limits.max_messages_scanned,
+        filter: select.filter.clone(),
+    });
+
+    let results = wait_for_results(&mut message_finder).await?;
+    info!(
+        "Found {} traces, in {} ms",
+        results.cache.iter_traces().len(),
+        results.time.num_milliseconds()
+    );
+
+    let options = RenderOptions {
+        layout: GraphLayout::Overlay,
+        size: (output.width, output.height),
+        bounds: Bounds::default(),
+        conversion: None,
+        scale: scale.clone(),
+        processing: processing.clone(),
+    };
+    let num_saved = graphics::save_cache::<G>(
+        output.format,
+        &output.path,
+        &results.cache,
+        &options,
+        bounds,
+        units,
+    )?;
+
+    if num_saved == 0 {
+        anyhow::bail!("No traces were found.");
+    }
+    Ok(num_saved)
+}
+
+/// Waits on the message finder until the search ends, logging its status as it progresses.
+///
+/// Each status is taken as soon as it is sent, as the search waits for it to be taken before it continues.
+async fn wait_for_results<M: MessageFinder>(
+    message_finder: &mut M,
+) -> anyhow::Result<SearchResults> {
+    let mut sigint = signal(SignalKind::interrupt())?;
+    loop {
+        tokio::select! {
+            _ = message_finder.wait_for_update() => {},
+            _ = sigint.recv() => {
+                message_finder.halt_search();
+            }
+        }
+
+        match message_finder.status() {
+            Some(SearchStatus::Halted) => warn!("Search halted."),
+            Some(SearchStatus::TimedOut(secs)) => warn!("Search timed out after {secs} s."),
+            Some(SearchStatus::ScanLimitReached(number)) => {
+                warn!("Search ended after scanning {number} messages.")
+            }
+            Some(SearchStatus::Successful) => info!("Search complete."),
+            _ => {}
+        }
+
+        if let Some(results) = message_finder.results() {
+            return Ok(results);
+        }
+    }
+}
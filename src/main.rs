@@ -4,13 +4,14 @@ mod app;
 mod cli_structs;
 mod finder;
 mod graphics;
+mod headless;
 mod messages;
 mod tui;
 
 use chrono::{DateTime, Utc};
 use clap::Parser;
 use crossterm::{
-    event::{self, Event},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{self, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -33,9 +34,10 @@ use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Layer};
 
 use crate::{
     app::{App, AppDependencies},
-    cli_structs::{Select, Topics, UserBounds},
+    cli_structs::{DetectorSettings, IntensityScale, Mode, Select, Topics, Units, UserBounds},
     finder::{MessageFinder, SearchEngine},
-    graphics::{GraphSaver, SvgSaver},
+    graphics::{GraphSaver, PlottersSaver},
+    messages::Pipelines,
     tui::{Component, InputComponent},
 };
 
@@ -58,6 +60,21 @@ struct Cli {
     #[clap(flatten)]
     bounds: UserBounds,
 
+    #[clap(flatten)]
+    units: Units,
+
+    #[clap(flatten)]
+    scale: IntensityScale,
+
+    /// Comma separated chains of processors, each graphed alongside every trace, e.g. `ma:8,ma:8>diff`.
+    /// The processors of a chain are separated by `>`, and are `ma:<samples>` (moving average),
+    /// `exp:<weight>` (exponential smoothing), `diff` (first derivative) and `baseline:<samples>` (baseline removal).
+    #[clap(long, default_value = "none")]
+    processing: Pipelines,
+
+    #[clap(flatten)]
+    detector: DetectorSettings,
+
     /// If set, then OpenTelemetry data is sent to the URL specified, otherwise the standard tracing subscriber is used.
     #[clap(long)]
     otel_endpoint: Option<String>,
@@ -75,10 +92,9 @@ struct Cli {
     ///// Which data to collect.
     //#[clap(long)]
     //collect: CollectType,
-
-    ///// Subcommand to execute.
-    //#[command(subcommand)]
-    //mode: Mode,
+    /// If set, the search is performed without a terminal, otherwise the interactive terminal interface is used.
+    #[command(subcommand)]
+    mode: Option<Mode>,
 }
 
 pub fn create_default_consumer(
@@ -110,7 +126,7 @@ struct TheAppDependencies;
 
 impl AppDependencies for TheAppDependencies {
     type MessageFinder = SearchEngine;
-    type GraphSaver = SvgSaver;
+    type GraphSaver = PlottersSaver;
 }
 
 /// Entry point.
@@ -146,15 +162,38 @@ async fn main() -> anyhow::Result<()> {
         None,
     )?;
 
+    if let Some(Mode::File(output)) = &args.mode {
+        let search_engine = SearchEngine::new(consumer, &args.select, &args.topics);
+        headless::output_to_file::<_, PlottersSaver>(
+            search_engine,
+            &args.select,
+            &args.bounds,
+            &args.units,
+            &args.scale,
+            &args.processing,
+            output,
+        )
+        .await?;
+        return Ok(());
+    }
+
     // Set up terminal.
     terminal::enable_raw_mode()?;
     let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     let search_engine = SearchEngine::new(consumer, &args.select, &args.topics);
-    let mut app = App::<TheAppDependencies>::new(search_engine, &args.select);
+    let mut app = App::<TheAppDependencies>::new(
+        search_engine,
+        &args.select,
+        &args.bounds,
+        &args.units,
+        &args.scale,
+        &args.processing,
+        &args.detector,
+    );
 
     let mut sigint = signal(SignalKind::interrupt())?;
 
@@ -167,14 +206,20 @@ async fn main() -> anyhow::Result<()> {
     loop {
         tokio::select! {
             _ = app_update.tick() => {
-                match event::poll(time::Duration::from_millis(10)) {
-                    Ok(true) => match event::read() {
-                        Ok(Event::Key(key)) => app.handle_key_press(key),
+                // Handle every waiting event, as dragging the mouse generates many at once.
+                let mut timeout = time::Duration::from_millis(10);
+                loop {
+                    match event::poll(timeout) {
+                        Ok(true) => match event::read() {
+                            Ok(Event::Key(key)) => app.handle_key_press(key),
+                            Ok(Event::Mouse(mouse)) => app.handle_mouse_event(mouse),
+                            Err(e) => panic!("{e}"),
+                            _ => {}
+                        },
                         Err(e) => panic!("{e}"),
-                        _ => {}
-                    },
-                    Err(e) => panic!("{e}"),
-                    _ => {}
+                        _ => break,
+                    }
+                    timeout = time::Duration::ZERO;
                 }
                 if app.changed() {
                     terminal.draw(|frame|app.render(frame, frame.area()))?;
@@ -194,36 +239,12 @@ async fn main() -> anyhow::Result<()> {
     }
     // Clean up terminal.
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
     terminal.clear()?;
-    /*
-    let trace = find_engine.find(&trace_finder, 1, args.select.timestamp, |x|x.has_channel(args.select.channel));
-    let digitiser_id = trace.as_ref().expect("").digitiser_id();
-    let eventlist = find_engine.find(&eventlist_finder, 1, args.select.timestamp, |evlist|evlist.digitiser_id() == digitiser_id);
-
-    if let Some((trace,eventlist)) = Option::zip(trace,eventlist) {
-        cache.push_trace(&trace.get_unpacked_message().expect(""));
-        cache.push_event_list_to_trace(&eventlist.get_unpacked_message().expect(""));
-        match args.mode {
-            Mode::File(output_to_file) => {
-                info!("Outputting {} Digitiser Traces", cache.iter_traces().len());
-                for (metadata, traces) in cache.iter_traces() {
-                    info!("Outputting Frame {:?} Traces", metadata);
-                    info!("Outputting {} Traces", traces.traces.len());
-                    for (channel, trace) in &traces.traces {
-                        info!("Outputting Channel {channel}");
-                        let mut bounds = Bounds::from_trace(&trace).expect("");
-                        bounds.ammend_with_user_input(&args.bounds);
-                        let graph = BuildGraph::<BackendSVG<'_>>::new(800,600,bounds.time_range(), bounds.intensity_range());
-
-                        let path_buf = graph.build_path(&output_to_file.path, metadata, *channel).expect("extension should write");
-                        let eventlist = traces.events.as_ref().and_then(|ev|ev.get(channel));
-                        graph.save_trace_graph(&path_buf, &trace, eventlist).expect("");
-                    }
-                }
-            },
-        }
-    } */
     Ok(())
 }
@@ -1,20 +1,27 @@
 //!
+use chrono::TimeDelta;
 use std::collections::{
-    hash_map::{self, Entry},
-    HashMap,
+    btree_map::{self, Entry},
+    BTreeMap, BTreeSet,
 };
+use supermusr_common::Channel;
 use supermusr_streaming_types::{
     dat2_digitizer_analog_trace_v2_generated::DigitizerAnalogTraceMessage,
     dev2_digitizer_event_v2_generated::DigitizerEventListMessage,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::messages::{CreateFromMessage, DigitiserEventList, DigitiserMetadata, DigitiserTrace};
+use crate::messages::{
+    CreateFromMessage, DigitiserEventList, DigitiserMetadata, DigitiserTrace, MessageSource,
+};
 
-#[derive(Default)]
+/// Stores the traces and event lists found by a search, ordered by timestamp.
+#[derive(Default, Clone)]
 pub(crate) struct Cache {
-    traces: HashMap<DigitiserMetadata, DigitiserTrace>,
-    events: HashMap<DigitiserMetadata, DigitiserEventList>,
+    traces: BTreeMap<DigitiserMetadata, DigitiserTrace>,
+    events: BTreeMap<DigitiserMetadata, DigitiserEventList>,
+    /// The event lists which could not be paired with a trace by [Self::attach_event_lists_to_trace].
+    unpaired_events: Vec<DigitiserMetadata>,
 }
 
 impl Cache {
@@ -23,47 +30,57 @@ impl Cache {
         self.events.clear();
     }*/
 
-    pub(crate) fn push_trace(&mut self, msg: &DigitizerAnalogTraceMessage<'_>) {
+    pub(crate) fn push_trace(
+        &mut self,
+        msg: &DigitizerAnalogTraceMessage<'_>,
+        source: MessageSource,
+    ) {
         info!("New Trace");
-        let metadata = DigitiserMetadata {
-            id: msg.digitizer_id(),
-            timestamp: msg
-                .metadata()
-                .timestamp()
-                .unwrap()
-                .clone()
-                .try_into()
-                .unwrap(),
-        };
+        let metadata = DigitiserMetadata::new(msg.digitizer_id(), &msg.metadata()).expect("");
         match self.traces.entry(metadata) {
             Entry::Occupied(occupied_entry) => {
                 error!("Trace already found: {0:?}", occupied_entry.key());
             }
             Entry::Vacant(vacant_entry) => {
-                vacant_entry.insert(DigitiserTrace::create_from_message(msg));
+                let mut trace = DigitiserTrace::create_from_message(msg);
+                trace.source = Some(source);
+                vacant_entry.insert(trace);
             }
         }
     }
 
-    pub(crate) fn iter_traces(&self) -> hash_map::Iter<'_, DigitiserMetadata, DigitiserTrace> {
+    pub(crate) fn iter_traces(&self) -> btree_map::Iter<'_, DigitiserMetadata, DigitiserTrace> {
         self.traces.iter()
     }
 
-    pub(crate) fn iter_events(&self) -> hash_map::Iter<'_, DigitiserMetadata, DigitiserEventList> {
+    /// Returns the trace with the given metadata, and the metadata as it is stored, if present.
+    pub(crate) fn get_trace(
+        &self,
+        metadata: &DigitiserMetadata,
+    ) -> Option<(&DigitiserMetadata, &DigitiserTrace)> {
+        self.traces.get_key_value(metadata)
+    }
+
+    /// Returns the event list with the given metadata, if present.
+    pub(crate) fn get_events(&self, metadata: &DigitiserMetadata) -> Option<&DigitiserEventList> {
+        self.events.get(metadata)
+    }
+
+    pub(crate) fn iter_events(&self) -> btree_map::Iter<'_, DigitiserMetadata, DigitiserEventList> {
         self.events.iter()
     }
 
+    /// Iterates over the event lists which could not be paired with a trace.
+    pub(crate) fn iter_unpaired_events(
+        &self,
+    ) -> impl Iterator<Item = (&DigitiserMetadata, &DigitiserEventList)> {
+        self.unpaired_events
+            .iter()
+            .filter_map(|metadata| self.events.get_key_value(metadata))
+    }
+
     pub(crate) fn push_events(&mut self, msg: &DigitizerEventListMessage<'_>) {
-        let metadata = DigitiserMetadata {
-            id: msg.digitizer_id(),
-            timestamp: msg
-                .metadata()
-                .timestamp()
-                .unwrap()
-                .clone()
-                .try_into()
-                .unwrap(),
-        };
+        let metadata = DigitiserMetadata::new(msg.digitizer_id(), &msg.metadata()).expect("");
         match self.events.entry(metadata) {
             Entry::Occupied(occupied_entry) => {
                 error!("Event list already found: {0:?}", occupied_entry.key());
@@ -74,17 +91,210 @@ impl Cache {
         }
     }
 
-    pub(crate) fn attach_event_lists_to_trace(&mut self) {
+    /// Removes the oldest traces and event lists, until at most `number` distinct metadata remain.
+    ///
+    /// A trace and event list with the same metadata are kept or removed together, so that neither is orphaned.
+    ///
+    /// # Attributes
+    /// - number: the number of distinct metadata to keep.
+    pub(crate) fn keep_latest(&mut self, number: usize) {
+        let metadata: BTreeSet<&DigitiserMetadata> =
+            self.traces.keys().chain(self.events.keys()).collect();
+        let evicted: Vec<DigitiserMetadata> =
+            metadata.into_iter().rev().skip(number).cloned().collect();
+        for metadata in &evicted {
+            self.traces.remove(metadata);
+            self.events.remove(metadata);
+        }
+        self.unpaired_events
+            .retain(|metadata| self.events.contains_key(metadata));
+    }
+
+    /// Removes the event lists which [Self::attach_event_lists_to_trace] could not pair with a trace.
+    pub(crate) fn remove_unpaired_events(&mut self) {
+        for metadata in self.unpaired_events.drain(..) {
+            self.events.remove(&metadata);
+        }
+    }
+
+    /// Removes every channel not in `channels` from the traces and event lists.
+    ///
+    /// # Attributes
+    /// - channels: the channels to keep.
+    pub(crate) fn retain_channels(&mut self, channels: &[Channel]) {
+        for trace in self.traces.values_mut() {
+            trace.traces.retain(|c, _| channels.contains(c));
+            if let Some(events) = &mut trace.events {
+                events.retain(|c, _| channels.contains(c));
+            }
+        }
+        for events in self.events.values_mut() {
+            events.retain(|c, _| channels.contains(c));
+        }
+    }
+
+    /// Attaches each event list to the trace it was formed from, pairing each trace with at most one event list.
+    ///
+    /// An event list is paired with the trace whose metadata matches exactly, if there is one.
+    /// Otherwise it is paired with the trace of the same frame, period and digitiser, not already paired,
+    /// whose timestamp is nearest, and within `tolerance`, of the event list's.
+    ///
+    /// # Attributes
+    /// - tolerance: the maximum difference between the timestamps of a paired trace and event list.
+    pub(crate) fn attach_event_lists_to_trace(&mut self, tolerance: TimeDelta) {
+        self.unpaired_events.clear();
+        for trace in self.traces.values_mut() {
+            trace.events = None;
+        }
+
+        // Exact matches are paired first, so that no trace is taken by a near match which belongs to another.
+        let mut unmatched = Vec::new();
         for (metadata, events) in &self.events {
-            match self.traces.entry(metadata.clone()) {
-                Entry::Occupied(mut occupied_entry) => {
+            match self.traces.get_mut(metadata) {
+                Some(trace) => {
+                    info!("Found Trace for Events");
+                    trace.events = Some(events.clone());
+                }
+                None => unmatched.push((metadata, events)),
+            }
+        }
+
+        for (metadata, events) in unmatched {
+            let trace = self
+                .traces
+                .iter_mut()
+                .filter(|(m, trace)| trace.events.is_none() && m.is_same_frame(metadata))
+                .map(|(m, trace)| ((m.timestamp - metadata.timestamp).abs(), trace))
+                .filter(|(delta, _)| *delta <= tolerance)
+                .min_by_key(|(delta, _)| *delta)
+                .map(|(_, trace)| trace);
+            match trace {
+                Some(trace) => {
                     info!("Found Trace for Events");
-                    occupied_entry.get_mut().events = Some(events.clone());
+                    trace.events = Some(events.clone());
                 }
-                Entry::Vacant(vacant_entry) => {
-                    error!("Trace not found: {0:?}", vacant_entry.key());
+                None => {
+                    warn!("Trace not found: {metadata:?}");
+                    self.unpaired_events.push(metadata.clone());
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::messages::Event;
+
+    const TIMESTAMP_NS: i64 = 1_700_000_000_000_000_000;
+
+    fn metadata(frame_number: u32, offset_ns: i64) -> DigitiserMetadata {
+        DigitiserMetadata {
+            timestamp: DateTime::from_timestamp_nanos(TIMESTAMP_NS + offset_ns),
+            id: 4,
+            frame_number,
+            period_number: 0,
+            protons_per_pulse: 0,
+            running: true,
+            veto_flags: 0,
+        }
+    }
+
+    fn trace() -> DigitiserTrace {
+        DigitiserTrace {
+            traces: HashMap::from([(0, vec![0, 10, 0])]),
+            events: None,
+            sample_rate: None,
+            source: None,
+        }
+    }
+
+    fn events() -> DigitiserEventList {
+        HashMap::from([(
+            0,
+            vec![Event {
+                time: 1,
+                intensity: 10,
+            }],
+        )])
+    }
+
+    fn is_paired(cache: &Cache, metadata: &DigitiserMetadata) -> bool {
+        cache
+            .get_trace(metadata)
+            .is_some_and(|(_, trace)| trace.events.is_some())
+    }
+
+    #[test]
+    fn pair_exact_match() {
+        let mut cache = Cache::default();
+        cache.traces.insert(metadata(1, 0), trace());
+        cache.events.insert(metadata(1, 0), events());
+
+        cache.attach_event_lists_to_trace(TimeDelta::zero());
+        assert!(is_paired(&cache, &metadata(1, 0)));
+        assert_eq!(cache.iter_unpaired_events().count(), 0);
+    }
+
+    #[test]
+    fn pair_within_tolerance() {
+        let mut cache = Cache::default();
+        cache.traces.insert(metadata(1, 0), trace());
+        cache.events.insert(metadata(1, 500_000), events());
+
+        cache.attach_event_lists_to_trace(TimeDelta::milliseconds(1));
+        assert!(is_paired(&cache, &metadata(1, 0)));
+        assert_eq!(cache.iter_unpaired_events().count(), 0);
+    }
+
+    #[test]
+    fn unpaired_outside_tolerance() {
+        let mut cache = Cache::default();
+        cache.traces.insert(metadata(1, 0), trace());
+        cache.events.insert(metadata(1, 2_000_000), events());
+
+        cache.attach_event_lists_to_trace(TimeDelta::milliseconds(1));
+        assert!(!is_paired(&cache, &metadata(1, 0)));
+        let unpaired: Vec<_> = cache
+            .iter_unpaired_events()
+            .map(|(metadata, _)| metadata.clone())
+            .collect();
+        assert_eq!(unpaired, vec![metadata(1, 2_000_000)]);
+
+        cache.remove_unpaired_events();
+        assert_eq!(cache.iter_events().count(), 0);
+        assert_eq!(cache.iter_traces().count(), 1);
+    }
+
+    #[test]
+    fn unpaired_other_frame() {
+        let mut cache = Cache::default();
+        cache.traces.insert(metadata(1, 0), trace());
+        cache.events.insert(metadata(2, 500_000), events());
+
+        cache.attach_event_lists_to_trace(TimeDelta::milliseconds(1));
+        assert!(!is_paired(&cache, &metadata(1, 0)));
+        assert_eq!(cache.iter_unpaired_events().count(), 1);
+    }
+
+    #[test]
+    fn pair_nearest_unpaired_trace() {
+        let mut cache = Cache::default();
+        cache.traces.insert(metadata(1, 0), trace());
+        cache.traces.insert(metadata(1, 300_000), trace());
+        cache.traces.insert(metadata(1, 800_000), trace());
+        cache.events.insert(metadata(1, 0), events());
+        cache.events.insert(metadata(1, 200_000), events());
+
+        // The exact match keeps its trace, so the near event list takes the nearest of the others.
+        cache.attach_event_lists_to_trace(TimeDelta::milliseconds(1));
+        assert!(is_paired(&cache, &metadata(1, 0)));
+        assert!(is_paired(&cache, &metadata(1, 300_000)));
+        assert!(!is_paired(&cache, &metadata(1, 800_000)));
+        assert_eq!(cache.iter_unpaired_events().count(), 0);
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+
+use strum::{Display, EnumString};
+use supermusr_common::{Intensity, Time};
+
+use crate::messages::{Event, EventList, Trace};
+
+/// The direction in which a detector's pulses go from its baseline.
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumString, Display)]
+pub(crate) enum Polarity {
+    /// Pulses rise above the baseline.
+    #[default]
+    #[strum(to_string = "positive")]
+    Positive,
+    /// Pulses fall below the baseline.
+    #[strum(to_string = "negative")]
+    Negative,
+}
+
+/// Detects events in a trace where it rises to a threshold, in the manner of the event formation
+/// of the pipeline, so that threshold settings can be previewed against traces already found.
+///
+/// As in the pipeline, each sample is first taken relative to the baseline, in the direction of the polarity,
+/// and it is this height which is compared to the threshold.
+/// The discriminator is constant phase, so each event is timed at the sample the trace crossed the threshold.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ThresholdDetector {
+    /// The direction in which pulses go from the baseline.
+    polarity: Polarity,
+    /// The raw intensity of the trace when there is no pulse.
+    baseline: Intensity,
+    /// The height above the baseline, in the direction of the polarity, at or above which the trace must be for an event.
+    threshold: Intensity,
+    /// The number of consecutive samples the trace must stay at or above the threshold for an event.
+    duration: usize,
+    /// The number of samples after each event is detected during which no other event is detected.
+    cool_off: usize,
+}
+
+impl ThresholdDetector {
+    /// Creates a new detector.
+    ///
+    /// # Attributes
+    /// - polarity: the direction in which pulses go from the baseline.
+    /// - baseline: the raw intensity of the trace when there is no pulse.
+    /// - threshold: the height above the baseline, in the direction of the polarity, at or above which the trace must be for an event.
+    /// - duration: the number of consecutive samples it must stay there, at least one.
+    /// - cool_off: the number of samples after each event during which no other is detected.
+    pub(crate) fn new(
+        polarity: Polarity,
+        baseline: Intensity,
+        threshold: Intensity,
+        duration: usize,
+        cool_off: usize,
+    ) -> Self {
+        Self {
+            polarity,
+            baseline,
+            threshold,
+            duration: duration.max(1),
+            cool_off,
+        }
+    }
+
+    /// Returns the height of the raw intensity above the baseline, in the direction of the polarity.
+    fn height(&self, intensity: Intensity) -> i32 {
+        let difference = intensity as i32 - self.baseline as i32;
+        match self.polarity {
+            Polarity::Positive => difference,
+            Polarity::Negative => -difference,
+        }
+    }
+
+    /// Returns the events detected in the trace, in order of time.
+    ///
+    /// Each event has the time and raw intensity of the sample at which the trace crossed the threshold.
+    pub(crate) fn detect(&self, trace: &Trace) -> EventList {
+        let mut events = EventList::new();
+        // The sample at which the trace last crossed the threshold, if it is still above it.
+        let mut crossing: Option<usize> = None;
+        // No event is detected in a crossing before this sample.
+        let mut cool_off_end = 0;
+        for (index, &intensity) in trace.iter().enumerate() {
+            if self.height(intensity) < self.threshold as i32 {
+                crossing = None;
+                continue;
+            }
+            let start = *crossing.get_or_insert(index);
+            if index + 1 - start == self.duration && start >= cool_off_end {
+                events.push(Event {
+                    time: start as Time,
+                    intensity: trace[start],
+                });
+                cool_off_end = index + 1 + self.cool_off;
+            }
+        }
+        events
+    }
+}
+
+/// The agreement between events detected locally and those of the broker.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct EventComparison {
+    /// The number of detected events which match an event of the broker.
+    pub(crate) matched: usize,
+    /// The number of events of the broker which no detected event matches.
+    pub(crate) missed: usize,
+    /// The number of detected events which match no event of the broker.
+    pub(crate) extra: usize,
+}
+
+impl EventComparison {
+    /// Pairs detected events with those of the broker, each at most once, in order of time.
+    ///
+    /// # Attributes
+    /// - detected: the events detected locally.
+    /// - expected: the events of the broker.
+    /// - tolerance: the most samples apart two events can be to match.
+    pub(crate) fn new(detected: &EventList, expected: &EventList, tolerance: Time) -> Self {
+        let sorted_times = |events: &EventList| {
+            let mut times: Vec<Time> = events.iter().map(|event| event.time).collect();
+            times.sort();
+            times
+        };
+        let (detected, expected) = (sorted_times(detected), sorted_times(expected));
+
+        let mut matched = 0;
+        let (mut d, mut e) = (0, 0);
+        while d < detected.len() && e < expected.len() {
+            if detected[d].abs_diff(expected[e]) <= tolerance {
+                matched += 1;
+                d += 1;
+                e += 1;
+            } else if detected[d] < expected[e] {
+                d += 1;
+            } else {
+                e += 1;
+            }
+        }
+        Self {
+            matched,
+            missed: expected.len() - matched,
+            extra: detected.len() - matched,
+        }
+    }
+}
+
+impl fmt::Display for EventComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "matched: {}, missed: {}, extra: {}",
+            self.matched, self.missed, self.extra
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(events: &EventList) -> Vec<Time> {
+        events.iter().map(|event| event.time).collect()
+    }
+
+    fn events_at(times: &[Time]) -> EventList {
+        times
+            .iter()
+            .map(|&time| Event { time, intensity: 0 })
+            .collect()
+    }
+
+    #[test]
+    fn detect_positive() {
+        let detector = ThresholdDetector::new(Polarity::Positive, 0, 5, 1, 0);
+        let trace = vec![0, 5, 6, 0, 4, 7, 0];
+        assert_eq!(times(&detector.detect(&trace)), vec![1, 5]);
+    }
+
+    #[test]
+    fn detect_negative_from_baseline() {
+        let detector = ThresholdDetector::new(Polarity::Negative, 100, 10, 1, 0);
+        let trace = vec![100, 95, 90, 100, 120, 85, 100];
+        let events = detector.detect(&trace);
+        assert_eq!(times(&events), vec![2, 5]);
+        assert_eq!(events[0].intensity, 90);
+    }
+
+    #[test]
+    fn detect_duration() {
+        let detector = ThresholdDetector::new(Polarity::Positive, 0, 5, 3, 0);
+        let trace = vec![0, 6, 6, 0, 6, 6, 6, 6, 0];
+        assert_eq!(times(&detector.detect(&trace)), vec![4]);
+    }
+
+    #[test]
+    fn detect_cool_off() {
+        let detector = ThresholdDetector::new(Polarity::Positive, 0, 5, 1, 3);
+        let trace = vec![6, 0, 6, 0, 0, 6, 0];
+        assert_eq!(times(&detector.detect(&trace)), vec![0, 5]);
+    }
+
+    #[test]
+    fn comparison_tolerance() {
+        let comparison = EventComparison::new(&events_at(&[10, 20]), &events_at(&[12, 25]), 2);
+        assert_eq!(
+            (comparison.matched, comparison.missed, comparison.extra),
+            (1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn comparison_one_to_one() {
+        let comparison = EventComparison::new(&events_at(&[10, 11]), &events_at(&[10]), 2);
+        assert_eq!(
+            (comparison.matched, comparison.missed, comparison.extra),
+            (1, 0, 1)
+        );
+
+        let comparison = EventComparison::new(&events_at(&[10]), &events_at(&[9, 11]), 2);
+        assert_eq!(
+            (comparison.matched, comparison.missed, comparison.extra),
+            (1, 1, 0)
+        );
+    }
+}
@@ -1,9 +1,13 @@
 //!
 mod cache;
+mod detector;
+mod processing;
+mod units;
 
 use chrono::{DateTime, Utc};
 use rdkafka::{message::BorrowedMessage, Message};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::{collections::HashMap, fmt::Display};
 use supermusr_common::{Channel, DigitizerId, Intensity, Time};
 use supermusr_streaming_types::{
     dat2_digitizer_analog_trace_v2_generated::{
@@ -14,9 +18,13 @@ use supermusr_streaming_types::{
         digitizer_event_list_message_buffer_has_identifier, root_as_digitizer_event_list_message,
         DigitizerEventListMessage,
     },
+    frame_metadata_v2_generated::FrameMetadataV2,
 };
 
 pub(crate) use cache::Cache;
+pub(crate) use detector::{EventComparison, Polarity, ThresholdDetector};
+pub(crate) use processing::{Pipeline, Pipelines, ProcessedTrace};
+pub(crate) use units::{AxisLabel, IntensityTransform, PointConversion, UnitConversion};
 
 /*
 #[derive(Clone)]
@@ -120,12 +128,84 @@ pub(crate) trait CreateFromMessage<M> {
 pub(crate) type Trace = Vec<Intensity>;
 
 /// Bundles all metadata which uniquely defines each digitiser message.
-#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+///
+/// Instances are ordered by timestamp first, and then by digitiser id.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize)]
 pub(crate) struct DigitiserMetadata {
-    /// Unique to each digitiser.
-    pub(crate) id: DigitizerId,
     /// Unique to each frame.
     pub(crate) timestamp: DateTime<Utc>,
+    /// Unique to each digitiser.
+    pub(crate) id: DigitizerId,
+    /// The number of the frame within its run.
+    pub(crate) frame_number: u32,
+    /// The period the frame belongs to.
+    pub(crate) period_number: u64,
+    /// The number of protons per pulse of the frame.
+    pub(crate) protons_per_pulse: u8,
+    /// Whether the run was in progress during the frame.
+    pub(crate) running: bool,
+    /// The reasons, if any, the frame was vetoed.
+    pub(crate) veto_flags: u16,
+}
+
+impl DigitiserMetadata {
+    /// Extracts the metadata of a digitiser message, or returns [None] if it has no valid timestamp.
+    ///
+    /// # Attributes
+    /// - id: the digitiser id of the message.
+    /// - metadata: the frame metadata of the message.
+    pub(crate) fn new(id: DigitizerId, metadata: &FrameMetadataV2) -> Option<Self> {
+        let timestamp = metadata
+            .timestamp()
+            .cloned()
+            .map(TryInto::try_into)
+            .and_then(Result::ok)?;
+
+        Some(Self {
+            timestamp,
+            id,
+            frame_number: metadata.frame_number(),
+            period_number: metadata.period_number(),
+            protons_per_pulse: metadata.protons_per_pulse(),
+            running: metadata.running(),
+            veto_flags: metadata.veto_flags(),
+        })
+    }
+
+    /// Returns a string with the frame metadata, other than the timestamp and digitiser id.
+    pub(crate) fn frame_info(&self) -> String {
+        format!(
+            "frame: {}, period: {}, ppp: {}, running: {}, veto: {:#06x}",
+            self.frame_number,
+            self.period_number,
+            self.protons_per_pulse,
+            self.running,
+            self.veto_flags
+        )
+    }
+
+    /// Returns true if both instances are of the same frame and period, from the same digitiser,
+    /// regardless of their timestamps.
+    pub(crate) fn is_same_frame(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.frame_number == other.frame_number
+            && self.period_number == other.period_number
+    }
+}
+
+/// The location on the broker at which a message was found.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MessageSource {
+    /// The partition of the topic the message was found on.
+    pub(crate) partition: i32,
+    /// The offset of the message within its partition.
+    pub(crate) offset: i64,
+}
+
+impl Display for MessageSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "partition: {}, offset: {}", self.partition, self.offset)
+    }
 }
 
 /// Encapsulates all traces of a digitiser trace message.
@@ -134,6 +214,19 @@ pub(crate) struct DigitiserTrace {
     ///
     pub(crate) traces: HashMap<Channel, Trace>,
     pub(crate) events: Option<DigitiserEventList>,
+    /// The sample rate of the traces in Hz, if the message gives one.
+    pub(crate) sample_rate: Option<u64>,
+    /// The location of the trace message on the broker, if known.
+    pub(crate) source: Option<MessageSource>,
+}
+
+impl DigitiserTrace {
+    /// Returns the channels of the trace, in order.
+    pub(crate) fn channels(&self) -> Vec<Channel> {
+        let mut channels: Vec<Channel> = self.traces.keys().copied().collect();
+        channels.sort();
+        channels
+    }
 }
 
 impl CreateFromMessage<DigitizerAnalogTraceMessage<'_>> for DigitiserTrace {
@@ -148,11 +241,13 @@ impl CreateFromMessage<DigitizerAnalogTraceMessage<'_>> for DigitiserTrace {
         DigitiserTrace {
             traces,
             events: None,
+            sample_rate: Some(msg.sample_rate()).filter(|&sample_rate| sample_rate != 0),
+            source: None,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
 pub(crate) struct Event {
     pub(crate) time: Time,
     pub(crate) intensity: Intensity,
@@ -183,12 +278,13 @@ pub(crate) trait FBMessage<'a>: Sized {
     fn get_unpacked_message(&'a self) -> Option<Self::UnpackedMessage>;
     fn timestamp(&self) -> DateTime<Utc>;
     fn digitiser_id(&self) -> DigitizerId;
+    fn metadata(&self) -> &DigitiserMetadata;
+    fn source(&self) -> MessageSource;
 }
 
 pub(crate) struct TraceMessage<'a> {
     message: BorrowedMessage<'a>,
-    timestamp: DateTime<Utc>,
-    digitiser_id: DigitizerId,
+    metadata: DigitiserMetadata,
 }
 
 impl<'a> TraceMessage<'a> {
@@ -210,35 +306,34 @@ impl<'a> FBMessage<'a> for TraceMessage<'a> {
     fn from_borrowed_message(message: BorrowedMessage<'a>) -> Option<Self> {
         let trace = message.unpack_trace_message()?;
 
-        let timestamp = trace
-            .metadata()
-            .timestamp()
-            .cloned()
-            .map(TryInto::try_into)
-            .map(Result::ok)
-            .flatten()?;
-        let digitiser_id = trace.digitizer_id();
+        let metadata = DigitiserMetadata::new(trace.digitizer_id(), &trace.metadata())?;
 
-        Some(Self {
-            message,
-            timestamp,
-            digitiser_id,
-        })
+        Some(Self { message, metadata })
     }
 
     fn timestamp(&self) -> DateTime<Utc> {
-        self.timestamp
+        self.metadata.timestamp
     }
 
     fn digitiser_id(&self) -> DigitizerId {
-        self.digitiser_id
+        self.metadata.id
+    }
+
+    fn metadata(&self) -> &DigitiserMetadata {
+        &self.metadata
+    }
+
+    fn source(&self) -> MessageSource {
+        MessageSource {
+            partition: self.message.partition(),
+            offset: self.message.offset(),
+        }
     }
 }
 
 pub(crate) struct EventListMessage<'a> {
     message: BorrowedMessage<'a>,
-    timestamp: DateTime<Utc>,
-    digitiser_id: DigitizerId,
+    metadata: DigitiserMetadata,
 }
 
 impl<'a> FBMessage<'a> for EventListMessage<'a> {
@@ -251,29 +346,28 @@ impl<'a> FBMessage<'a> for EventListMessage<'a> {
     fn from_borrowed_message(message: BorrowedMessage<'a>) -> Option<Self> {
         let evlist = message.unpack_event_list_message()?;
 
-        let timestamp = evlist
-            .metadata()
-            .timestamp()
-            .cloned()
-            .map(TryInto::try_into)
-            .map(Result::ok)
-            .flatten()?;
+        let metadata = DigitiserMetadata::new(evlist.digitizer_id(), &evlist.metadata())?;
 
-        let digitiser_id = evlist.digitizer_id();
-
-        Some(Self {
-            message,
-            timestamp,
-            digitiser_id,
-        })
+        Some(Self { message, metadata })
     }
 
     fn timestamp(&self) -> DateTime<Utc> {
-        self.timestamp
+        self.metadata.timestamp
     }
 
     fn digitiser_id(&self) -> DigitizerId {
-        self.digitiser_id
+        self.metadata.id
+    }
+
+    fn metadata(&self) -> &DigitiserMetadata {
+        &self.metadata
+    }
+
+    fn source(&self) -> MessageSource {
+        MessageSource {
+            partition: self.message.partition(),
+            offset: self.message.offset(),
+        }
     }
 }
 
@@ -0,0 +1,307 @@
+use std::{fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+use crate::messages::Trace;
+
+/// A trace after processing, whose values may be fractional or negative so are no longer raw intensities.
+pub(crate) type ProcessedTrace = Vec<f64>;
+
+/// Errors which can occur when parsing a [Processor], [Pipeline] or [Pipelines].
+#[derive(Debug, Error)]
+pub(crate) enum ProcessingError {
+    #[error("Unknown processor: {0}")]
+    UnknownProcessor(String),
+    #[error("Invalid parameter of {0}: {1}")]
+    InvalidParameter(&'static str, String),
+    #[error("Pipeline is empty")]
+    EmptyPipeline,
+}
+
+/// A single step of a [Pipeline], written as its name followed by its parameter if it has one, e.g. `ma:8`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Processor {
+    /// The mean of each sample and those preceding it, in a window of the given number of samples, e.g. `ma:8`.
+    MovingAverage(usize),
+    /// Exponential smoothing, with the given weight of each new sample between zero and one, e.g. `exp:0.2`.
+    Exponential(f64),
+    /// The difference between each sample and the one preceding it, written `diff`.
+    Derivative,
+    /// Subtracts the mean of the given number of samples at the start of the trace, e.g. `baseline:100`.
+    BaselineRemoval(usize),
+}
+
+impl Processor {
+    /// Returns the result of the processor on the values.
+    fn apply(&self, values: &[f64]) -> ProcessedTrace {
+        match *self {
+            Processor::MovingAverage(window) => {
+                let mut sum = 0.0;
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &value)| {
+                        sum += value;
+                        if index >= window {
+                            sum -= values[index - window];
+                        }
+                        sum / (index + 1).min(window) as f64
+                    })
+                    .collect()
+            }
+            Processor::Exponential(weight) => {
+                let mut smoothed = values.first().copied().unwrap_or_default();
+                values
+                    .iter()
+                    .map(|&value| {
+                        smoothed += weight * (value - smoothed);
+                        smoothed
+                    })
+                    .collect()
+            }
+            Processor::Derivative => values
+                .iter()
+                .scan(
+                    values.first().copied().unwrap_or_default(),
+                    |previous, &value| {
+                        let difference = value - *previous;
+                        *previous = value;
+                        Some(difference)
+                    },
+                )
+                .collect(),
+            Processor::BaselineRemoval(window) => {
+                let window = &values[..window.min(values.len())];
+                let baseline = if window.is_empty() {
+                    0.0
+                } else {
+                    window.iter().sum::<f64>() / window.len() as f64
+                };
+                values.iter().map(|value| value - baseline).collect()
+            }
+        }
+    }
+
+    /// Returns true if the result is a difference of intensities, rather than an intensity.
+    fn is_relative(&self) -> bool {
+        matches!(self, Processor::Derivative | Processor::BaselineRemoval(_))
+    }
+}
+
+impl FromStr for Processor {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, parameter) = match s.trim().split_once(':') {
+            Some((name, parameter)) => (name.trim(), Some(parameter.trim())),
+            None => (s.trim(), None),
+        };
+        let window = |name: &'static str| {
+            parameter
+                .and_then(|parameter| parameter.parse::<usize>().ok())
+                .filter(|&window| window > 0)
+                .ok_or_else(|| {
+                    ProcessingError::InvalidParameter(name, parameter.unwrap_or("").to_owned())
+                })
+        };
+        match name {
+            "ma" => window("ma").map(Processor::MovingAverage),
+            "baseline" => window("baseline").map(Processor::BaselineRemoval),
+            "exp" => parameter
+                .and_then(|parameter| parameter.parse::<f64>().ok())
+                .filter(|weight| 0.0 < *weight && *weight <= 1.0)
+                .map(Processor::Exponential)
+                .ok_or_else(|| {
+                    ProcessingError::InvalidParameter("exp", parameter.unwrap_or("").to_owned())
+                }),
+            "diff" if parameter.is_none() => Ok(Processor::Derivative),
+            _ => Err(ProcessingError::UnknownProcessor(s.trim().to_owned())),
+        }
+    }
+}
+
+impl Display for Processor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Processor::MovingAverage(window) => write!(f, "ma:{window}"),
+            Processor::Exponential(weight) => write!(f, "exp:{weight}"),
+            Processor::Derivative => write!(f, "diff"),
+            Processor::BaselineRemoval(window) => write!(f, "baseline:{window}"),
+        }
+    }
+}
+
+/// A chain of processors, each applied to the result of the last, written separated by `>`, e.g. `ma:8>diff`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Pipeline(Vec<Processor>);
+
+impl Pipeline {
+    /// Returns the result of every processor of the pipeline, in turn, on the trace.
+    pub(crate) fn apply(&self, trace: &Trace) -> ProcessedTrace {
+        let values: ProcessedTrace = trace.iter().map(|&value| value as f64).collect();
+        self.0
+            .iter()
+            .fold(values, |values, processor| processor.apply(&values))
+    }
+
+    /// Returns true if the result is a difference of intensities, so only the scale, and not the offset,
+    /// of a conversion into physical units applies to it.
+    pub(crate) fn is_relative(&self) -> bool {
+        self.0.iter().any(Processor::is_relative)
+    }
+}
+
+impl FromStr for Pipeline {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let processors = s
+            .split('>')
+            .filter(|processor| !processor.trim().is_empty())
+            .map(Processor::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if processors.is_empty() {
+            return Err(ProcessingError::EmptyPipeline);
+        }
+        Ok(Self(processors))
+    }
+}
+
+impl Display for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let processors: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+        write!(f, "{}", processors.join(">"))
+    }
+}
+
+/// The pipelines whose results are shown alongside each trace, written as comma separated [Pipeline]s,
+/// e.g. `ma:8,ma:8>diff`, or `none`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Pipelines(Vec<Pipeline>);
+
+impl Pipelines {
+    /// The pipelines of the list.
+    pub(crate) fn pipelines(&self) -> &[Pipeline] {
+        &self.0
+    }
+}
+
+impl FromStr for Pipelines {
+    type Err = ProcessingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "none" {
+            return Ok(Self::default());
+        }
+        s.split(',')
+            .filter(|pipeline| !pipeline.trim().is_empty())
+            .map(Pipeline::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+impl Display for Pipelines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "none")
+        } else {
+            let pipelines: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+            write!(f, "{}", pipelines.join(","))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn parse_processors() {
+        assert_eq!(
+            Processor::from_str("ma:8").unwrap(),
+            Processor::MovingAverage(8)
+        );
+        assert_eq!(
+            Processor::from_str(" exp : 0.2 ").unwrap(),
+            Processor::Exponential(0.2)
+        );
+        assert_eq!(Processor::from_str("diff").unwrap(), Processor::Derivative);
+        assert_eq!(
+            Processor::from_str("baseline:100").unwrap(),
+            Processor::BaselineRemoval(100)
+        );
+        for processor in ["ma:8", "exp:0.2", "diff", "baseline:100"] {
+            assert_eq!(
+                Processor::from_str(processor).unwrap().to_string(),
+                processor
+            );
+        }
+    }
+
+    #[test]
+    fn parse_invalid_processors() {
+        for processor in ["ma", "ma:0", "ma:x", "exp:0", "exp:1.5", "baseline:-1"] {
+            assert!(
+                matches!(
+                    Processor::from_str(processor),
+                    Err(ProcessingError::InvalidParameter(..))
+                ),
+                "{processor}"
+            );
+        }
+        for processor in ["median:3", "diff:2"] {
+            assert!(
+                matches!(
+                    Processor::from_str(processor),
+                    Err(ProcessingError::UnknownProcessor(_))
+                ),
+                "{processor}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_pipelines() {
+        let pipeline = Pipeline::from_str("ma:8>diff").unwrap();
+        assert_eq!(
+            pipeline,
+            Pipeline(vec![Processor::MovingAverage(8), Processor::Derivative])
+        );
+        assert!(pipeline.is_relative());
+        assert!(!Pipeline::from_str("ma:8").unwrap().is_relative());
+        assert!(matches!(
+            Pipeline::from_str(">"),
+            Err(ProcessingError::EmptyPipeline)
+        ));
+
+        let pipelines = Pipelines::from_str("ma:8,ma:8>diff").unwrap();
+        assert_eq!(pipelines.pipelines().len(), 2);
+        assert_eq!(pipelines.to_string(), "ma:8,ma:8>diff");
+        assert_eq!(Pipelines::from_str("none").unwrap(), Pipelines::default());
+        assert_eq!(Pipelines::default().to_string(), "none");
+    }
+
+    #[test]
+    fn apply_processors() {
+        let trace: Trace = vec![2, 4, 6, 8];
+        let apply = |pipeline: &str| Pipeline::from_str(pipeline).unwrap().apply(&trace);
+
+        for pipeline in ["ma:2", "exp:0.5", "diff", "baseline:2", "ma:2>diff>exp:0.5"] {
+            assert_eq!(apply(pipeline).len(), trace.len(), "{pipeline}");
+        }
+        assert_eq!(apply("ma:2"), vec![2.0, 3.0, 5.0, 7.0]);
+        assert_eq!(apply("diff"), vec![0.0, 2.0, 2.0, 2.0]);
+        assert_eq!(apply("baseline:2"), vec![-1.0, 1.0, 3.0, 5.0]);
+        assert_eq!(apply("ma:2>diff"), vec![0.0, 1.0, 2.0, 2.0]);
+        for (value, expected) in apply("exp:0.5").into_iter().zip([2.0, 3.0, 4.5, 6.25]) {
+            assert_approx_eq!(value, expected);
+        }
+        assert!(Pipeline::from_str("ma:2")
+            .unwrap()
+            .apply(&Trace::new())
+            .is_empty());
+    }
+}
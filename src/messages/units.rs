@@ -0,0 +1,272 @@
+use crate::{cli_structs::IntensityScale, messages::Pipeline};
+
+/// Converts the sample indices and raw intensities of a trace into nanoseconds and millivolts.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UnitConversion {
+    /// The time between consecutive samples.
+    ns_per_sample: f64,
+    /// The ADC scale of the digitiser.
+    mv_per_count: f64,
+    /// The ADC offset of the digitiser.
+    offset_mv: f64,
+}
+
+impl UnitConversion {
+    /// Creates a new conversion, or returns [None] if the sample rate is zero.
+    ///
+    /// # Attributes
+    /// - sample_rate: the sample rate of the trace, in Hz.
+    /// - mv_per_count: the ADC scale of the digitiser, in millivolts per count.
+    /// - offset_mv: the ADC offset of the digitiser, in millivolts.
+    pub(crate) fn new(sample_rate: u64, mv_per_count: f64, offset_mv: f64) -> Option<Self> {
+        (sample_rate != 0).then(|| Self {
+            ns_per_sample: 1e9 / sample_rate as f64,
+            mv_per_count,
+            offset_mv,
+        })
+    }
+
+    /// Converts a sample index to nanoseconds.
+    pub(crate) fn time(&self, sample: f64) -> f64 {
+        sample * self.ns_per_sample
+    }
+
+    /// Converts a raw intensity to millivolts.
+    pub(crate) fn intensity(&self, count: f64) -> f64 {
+        count * self.mv_per_count + self.offset_mv
+    }
+
+    /// Converts a difference of raw intensities to millivolts, to which the ADC offset does not apply.
+    pub(crate) fn intensity_difference(&self, count: f64) -> f64 {
+        count * self.mv_per_count
+    }
+
+    /// Converts nanoseconds to a sample index, the inverse of [Self::time].
+    pub(crate) fn raw_time(&self, ns: f64) -> f64 {
+        ns / self.ns_per_sample
+    }
+
+    /// Converts millivolts to a raw intensity, the inverse of [Self::intensity].
+    pub(crate) fn raw_intensity(&self, mv: f64) -> f64 {
+        (mv - self.offset_mv) / self.mv_per_count
+    }
+}
+
+/// A logarithmic scale which is also defined for zero and negative values, by being linear near zero.
+fn symmetric_log(value: f64) -> f64 {
+    value.signum() * value.abs().ln_1p() / std::f64::consts::LN_10
+}
+
+/// The inverse of [symmetric_log].
+fn symmetric_exp(value: f64) -> f64 {
+    value.signum() * (10.0_f64.powf(value.abs()) - 1.0)
+}
+
+/// Rescales the intensities of a trace, once they are in the units of the graph.
+///
+/// The baseline is subtracted first, then the intensity is negated if inverted, and finally put on a logarithmic scale.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct IntensityTransform {
+    /// Subtracted from each intensity.
+    baseline: f64,
+    /// If true, intensities are negated.
+    inverted: bool,
+    /// If true, intensities are put on a symmetric logarithmic scale.
+    logarithmic: bool,
+}
+
+impl IntensityTransform {
+    pub(crate) fn new(baseline: f64, inverted: bool, logarithmic: bool) -> Self {
+        Self {
+            baseline,
+            inverted,
+            logarithmic,
+        }
+    }
+
+    /// Returns the transform of differences of intensities, to which the baseline does not apply.
+    fn relative(&self) -> Self {
+        Self {
+            baseline: 0.0,
+            ..*self
+        }
+    }
+
+    /// Rescales an intensity.
+    pub(crate) fn apply(&self, intensity: f64) -> f64 {
+        let intensity = intensity - self.baseline;
+        let intensity = if self.inverted { -intensity } else { intensity };
+        if self.logarithmic {
+            symmetric_log(intensity)
+        } else {
+            intensity
+        }
+    }
+}
+
+/// Converts points of a trace, or of its processed traces, from sample indices and raw intensities
+/// into the units of the graph, and rescales their intensities.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PointConversion {
+    /// The conversion into physical units, if any.
+    conversion: Option<UnitConversion>,
+    /// The rescaling of intensities, once they are in physical units.
+    transform: IntensityTransform,
+    /// If true, intensities are differences of raw intensities.
+    relative: bool,
+}
+
+impl PointConversion {
+    pub(crate) fn new(conversion: Option<&UnitConversion>, transform: IntensityTransform) -> Self {
+        Self {
+            conversion: conversion.copied(),
+            transform,
+            relative: false,
+        }
+    }
+
+    /// Returns the conversion of the results of the pipeline.
+    ///
+    /// If the pipeline is relative, its results are differences of intensities,
+    /// in which the ADC offset and the baseline cancel out, so neither is applied.
+    pub(crate) fn for_pipeline(&self, pipeline: &Pipeline) -> Self {
+        if pipeline.is_relative() {
+            Self {
+                transform: self.transform.relative(),
+                relative: true,
+                ..*self
+            }
+        } else {
+            *self
+        }
+    }
+
+    /// Converts a sample index and raw intensity into the units of the graph.
+    pub(crate) fn apply(&self, time: f64, intensity: f64) -> (f64, f64) {
+        match &self.conversion {
+            Some(conversion) if self.relative => (
+                conversion.time(time),
+                self.transform
+                    .apply(conversion.intensity_difference(intensity)),
+            ),
+            Some(conversion) => (
+                conversion.time(time),
+                self.transform.apply(conversion.intensity(intensity)),
+            ),
+            None => (time, self.transform.apply(intensity)),
+        }
+    }
+}
+
+/// The title and label format of a graph axis.
+#[derive(Clone, Debug)]
+pub(crate) struct AxisLabel {
+    /// The quantity shown on the axis.
+    name: &'static str,
+    /// The unit of the labels, if there is one.
+    unit: Option<&'static str>,
+    /// Values are multiplied by this before they are shown as labels.
+    scale: f64,
+    /// If true, the values are rescaled from those of the trace.
+    rescaled: bool,
+    /// If true, the values are on a symmetric logarithmic scale, which is undone before they are shown as labels.
+    logarithmic: bool,
+}
+
+impl AxisLabel {
+    /// Time ranges at least this long, in nanoseconds, are labelled in microseconds.
+    const MICROSECOND_THRESHOLD: f64 = 10_000.0;
+
+    /// Returns the labels of the time and intensity axes.
+    ///
+    /// # Attributes
+    /// - conversion: the conversion applied to the graph, if any.
+    /// - time_range: the length of the time axis, in the units of the graph.
+    /// - scale: the scale of the intensity axis.
+    pub(crate) fn for_graph(
+        conversion: Option<&UnitConversion>,
+        time_range: f64,
+        scale: &IntensityScale,
+    ) -> (Self, Self) {
+        let time = match conversion {
+            Some(_) if time_range >= Self::MICROSECOND_THRESHOLD => {
+                Self::new("Time", Some("µs"), 1e-3)
+            }
+            Some(_) => Self::new("Time", Some("ns"), 1.0),
+            None => Self::new("Time", None, 1.0),
+        };
+        let name = match (scale.subtract_baseline, scale.invert_intensity) {
+            (false, false) => "Intensity",
+            (false, true) => "-Intensity",
+            (true, false) => "Intensity - baseline",
+            (true, true) => "Baseline - intensity",
+        };
+        let intensity = Self {
+            rescaled: !scale.is_linear(),
+            logarithmic: scale.log_intensity,
+            ..Self::new(name, conversion.map(|_| "mV"), 1.0)
+        };
+        (time, intensity)
+    }
+
+    fn new(name: &'static str, unit: Option<&'static str>, scale: f64) -> Self {
+        Self {
+            name,
+            unit,
+            scale,
+            rescaled: false,
+            logarithmic: false,
+        }
+    }
+
+    /// Returns true if the axis shows sample indices or raw intensities, as they are in the trace.
+    pub(crate) fn is_raw(&self) -> bool {
+        self.unit.is_none() && !self.rescaled
+    }
+
+    /// The title of the axis, including its unit and whether it is logarithmic.
+    pub(crate) fn title(&self) -> String {
+        let qualifiers: Vec<_> = self
+            .unit
+            .into_iter()
+            .chain(self.logarithmic.then_some("log scale"))
+            .collect();
+        if qualifiers.is_empty() {
+            self.name.to_owned()
+        } else {
+            format!("{} ({})", self.name, qualifiers.join(", "))
+        }
+    }
+
+    /// Returns the value, in the units of the graph, which a point of the axis is labelled with.
+    fn value(&self, value: f64) -> f64 {
+        if self.logarithmic {
+            symmetric_exp(value)
+        } else {
+            value
+        }
+    }
+
+    /// Formats a value, in the units of the graph, as a label of the axis.
+    pub(crate) fn format(&self, value: f64, precision: usize) -> String {
+        format!("{:.precision$}", self.value(value) * self.scale)
+    }
+
+    /// Formats a value, in the units of the graph, followed by the unit of the axis, if it has one.
+    pub(crate) fn readout(&self, value: f64) -> String {
+        self.readout_value(self.value(value))
+    }
+
+    /// Formats the difference between two values, in the units of the graph, followed by the unit of the axis, if it has one.
+    pub(crate) fn readout_difference(&self, value: f64, other: f64) -> String {
+        self.readout_value(self.value(value) - self.value(other))
+    }
+
+    /// Values without a unit are sample indices or raw intensities, so are shown as whole numbers.
+    fn readout_value(&self, value: f64) -> String {
+        match self.unit {
+            Some(unit) => format!("{:.3} {unit}", value * self.scale),
+            None => format!("{:.0}", value * self.scale),
+        }
+    }
+}
@@ -3,7 +3,7 @@ mod style;
 mod tui_component;
 mod widgets;
 
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
 use ratatui::{
     layout::{Alignment, Rect},
     widgets::{Block, BorderType},
@@ -15,7 +15,9 @@ pub(crate) use builder::TuiComponentBuilder;
 
 pub(crate) use style::ComponentStyle;
 pub(crate) use tui_component::TuiComponent;
-pub(crate) use widgets::{Channels, EditBox, Graph, GraphProperties, ListBox, Statusbar, TextBox};
+pub(crate) use widgets::{
+    Channels, EditBox, Graph, GraphData, GraphProperties, ListBox, Statusbar, TextBox, ZoomAxes,
+};
 
 /// Provides method to render any component in a [Frame]
 pub(crate) trait Component {
@@ -26,7 +28,7 @@ pub(crate) trait Component {
 /// Provides methods for components which contain other components, and have a `Focus` function.
 pub(crate) trait ComponentContainer: Component {
     /// The `enum` type which defines the child coponents.
-    type Focus: IntoEnumIterator + EnumCount;
+    type Focus: IntoEnumIterator + EnumCount + Clone;
 
     /// Maps each variant of [Self::Focus] to a type implementing [FocusableComponent].
     fn get_focused_component_mut(&mut self, focus: Self::Focus) -> &mut dyn FocusableComponent;
@@ -54,15 +56,50 @@ pub(crate) trait ComponentContainer: Component {
         );
         self.focused_component_mut().set_focus(true);
     }
+
+    /// Passes a mouse event to the child component under the mouse, giving it the focus if a button was pressed.
+    ///
+    /// Drags and releases are passed to the focused component instead, as they may stray outside of it.
+    fn route_mouse_event(&mut self, mouse: MouseEvent) {
+        if matches!(mouse.kind, MouseEventKind::Drag(_) | MouseEventKind::Up(_)) {
+            self.focused_component_mut().handle_mouse_event(mouse);
+            return;
+        }
+        let Some(focus) = Self::Focus::iter().find(|focus| {
+            self.get_focused_component_mut(focus.clone())
+                .is_under_mouse(&mouse)
+        }) else {
+            return;
+        };
+        if matches!(mouse.kind, MouseEventKind::Down(_)) {
+            self.focused_component_mut().set_focus(false);
+            self.set_focus(focus.clone());
+            self.focused_component_mut().set_focus(true);
+        }
+        self.get_focused_component_mut(focus)
+            .handle_mouse_event(mouse);
+    }
 }
 
-/// Provides method to handle user key events.
+/// Provides methods to handle user key and mouse events.
 ///
-/// This method does not return any value, so all results of user input should
+/// These methods do not return any value, so all results of user input should
 /// be stored as internal state.
 pub(crate) trait InputComponent: Component {
     ///
     fn handle_key_press(&mut self, key: KeyEvent);
+
+    /// Handles a mouse event, whose position is in the coordinates of the terminal.
+    ///
+    /// By default, mouse events are ignored.
+    fn handle_mouse_event(&mut self, _mouse: MouseEvent) {}
+
+    /// Returns whether the mouse event occurred over the area the component was last rendered in.
+    ///
+    /// By default, components are never under the mouse.
+    fn is_under_mouse(&self, _mouse: &MouseEvent) -> bool {
+        false
+    }
 }
 
 /// Provides handling for components which can be given the focus by the user
@@ -1,8 +1,11 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
 
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
-    layout::Rect,
+    layout::{Position, Rect},
     widgets::{Block, Borders},
     Frame,
 };
@@ -17,6 +20,8 @@ pub(crate) struct TuiComponent<C: Component + Sized> {
     parent_has_focus: bool,
     comp: C,
     config: TuiComponentBuilder,
+    /// The area the component was last rendered in, used to determine whether it is under the mouse.
+    area: Cell<Rect>,
 }
 
 impl<C: Component> TuiComponent<C> {
@@ -26,6 +31,7 @@ impl<C: Component> TuiComponent<C> {
             parent_has_focus: false,
             comp,
             config,
+            area: Cell::default(),
         }
     }
 
@@ -80,6 +86,7 @@ impl<C: ComponentContainer> ComponentContainer for TuiComponent<C> {
 
 impl<C: Component> Component for TuiComponent<C> {
     fn render(&self, frame: &mut Frame, area: Rect) {
+        self.area.set(area);
         if self.config.is_in_block {
             let block = Block::new()
                 .borders(Borders::ALL)
@@ -98,6 +105,16 @@ impl<C: InputComponent> InputComponent for TuiComponent<C> {
     fn handle_key_press(&mut self, key: KeyEvent) {
         self.comp.handle_key_press(key)
     }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        self.comp.handle_mouse_event(mouse)
+    }
+
+    fn is_under_mouse(&self, mouse: &MouseEvent) -> bool {
+        self.area
+            .get()
+            .contains(Position::new(mouse.column, mouse.row))
+    }
 }
 
 impl<C: FocusableComponent> FocusableComponent for TuiComponent<C> {
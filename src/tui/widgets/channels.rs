@@ -1,6 +1,8 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::cell::Cell;
+
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::Rect,
+    layout::{Position, Rect},
     style::{Color, Modifier, Style},
     widgets::Tabs,
     Frame,
@@ -21,6 +23,8 @@ pub(crate) struct Channels {
     parent_has_focus: bool,
     channels: Vec<Channel>,
     channel_index: usize,
+    /// The area the tabs were last rendered in, used to find the tab under the mouse.
+    area: Cell<Rect>,
 }
 
 impl Channels {
@@ -33,6 +37,7 @@ impl Channels {
                 has_focus: false,
                 parent_has_focus: false,
                 channel_index: 0,
+                area: Cell::default(),
             })
     }
 
@@ -41,6 +46,37 @@ impl Channels {
         self.channel_index = 0;
     }
 
+    /// Selects the given channel, if it is present.
+    pub(crate) fn select(&mut self, channel: Channel) {
+        if let Some(index) = self.channels.iter().position(|&c| c == channel) {
+            self.channel_index = index;
+        }
+    }
+
+    /// Returns the index of the tab shown at the given position, if there is one.
+    ///
+    /// This mirrors the layout of [Tabs], in which each title is padded by a space on either side,
+    /// and separated from the next by a one-column divider.
+    fn index_at(&self, position: Position) -> Option<usize> {
+        let area = self.area.get();
+        if !area.contains(position) {
+            return None;
+        }
+        let mut column = area.x;
+        for (index, title) in self.channels.iter().map(Self::title).enumerate() {
+            column += title.chars().count() as u16 + 2;
+            if position.x < column {
+                return Some(index);
+            }
+            column += 1;
+        }
+        None
+    }
+
+    fn title(channel: &Channel) -> String {
+        format!(" {channel} ")
+    }
+
     pub(crate) fn get(&self) -> Option<Channel> {
         if self.channels.is_empty() {
             None
@@ -62,13 +98,14 @@ impl Component for Channels {
             .fg(Color::Black)
             .add_modifier(Modifier::BOLD);
 
-        let tabs = Tabs::new(self.channels.iter().map(|c| format!(" {c} ")))
+        let tabs = Tabs::new(self.channels.iter().map(Self::title))
             .style(style)
             .highlight_style(select_style)
             //.divider(symbols::line::THICK_VERTICAL)
             .select(self.channel_index);
 
         frame.render_widget(tabs, area);
+        self.area.set(area);
     }
 }
 
@@ -86,6 +123,14 @@ impl InputComponent for Channels {
             }
         }
     }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+            if let Some(index) = self.index_at(Position::new(mouse.column, mouse.row)) {
+                self.channel_index = index;
+            }
+        }
+    }
 }
 
 impl ParentalFocusComponent for Channels {
@@ -0,0 +1,77 @@
+use crate::graphics::Bounds;
+
+/// A cursor of the [Graph](super::Graph), which is always snapped to a point of the data.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Cursor {
+    /// The cursor is on the trace sample with the given index.
+    Sample(usize),
+    /// The cursor is on the event with the given index in the event list.
+    Event(usize),
+}
+
+impl Cursor {
+    /// Returns the point the cursor is snapped to, or [None] if it is no longer in the data.
+    ///
+    /// # Attributes
+    /// - trace_data: the trace values of the graph.
+    /// - event_data: the event list of the graph, if present.
+    pub(super) fn point(
+        &self,
+        trace_data: &[(f64, f64)],
+        event_data: Option<&[(f64, f64)]>,
+    ) -> Option<(f64, f64)> {
+        match *self {
+            Cursor::Sample(index) => trace_data.get(index).copied(),
+            Cursor::Event(index) => event_data.and_then(|events| events.get(index).copied()),
+        }
+    }
+}
+
+/// Returns the index of the trace sample nearest in time to `time`.
+///
+/// The samples are assumed to be in order of time, which they are as they are constructed from sample indices.
+pub(super) fn nearest_sample(trace_data: &[(f64, f64)], time: f64) -> Option<usize> {
+    if trace_data.is_empty() {
+        return None;
+    }
+    let after = trace_data.partition_point(|(t, _)| *t < time);
+    if after == 0 {
+        Some(0)
+    } else if after == trace_data.len() {
+        Some(after - 1)
+    } else if trace_data[after].0 - time < time - trace_data[after - 1].0 {
+        Some(after)
+    } else {
+        Some(after - 1)
+    }
+}
+
+/// Returns the index of the first event after `time`, or the last event before it if `forwards` is false.
+///
+/// Event lists are not assumed to be in order of time.
+pub(super) fn adjacent_event(
+    event_data: &[(f64, f64)],
+    time: f64,
+    forwards: bool,
+) -> Option<usize> {
+    let candidates = event_data.iter().enumerate();
+    if forwards {
+        candidates
+            .filter(|(_, (t, _))| *t > time)
+            .min_by(|(_, (t1, _)), (_, (t2, _))| t1.total_cmp(t2))
+            .map(|(index, _)| index)
+    } else {
+        candidates
+            .filter(|(_, (t, _))| *t < time)
+            .max_by(|(_, (t1, _)), (_, (t2, _))| t1.total_cmp(t2))
+            .map(|(index, _)| index)
+    }
+}
+
+/// Returns the vertical and horizontal lines of a crosshair through the point, spanning the bounds.
+pub(super) fn crosshair((time, intensity): (f64, f64), bounds: &Bounds) -> [[(f64, f64); 2]; 2] {
+    [
+        [(time, bounds.intensity.min), (time, bounds.intensity.max)],
+        [(bounds.time.min, intensity), (bounds.time.max, intensity)],
+    ]
+}
@@ -1,20 +1,56 @@
+use std::cell::Cell;
+
+use crossterm::event::{KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     style::{Color, Style},
     symbols::Marker,
-    widgets::{Chart, Dataset, GraphType, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    widgets::{Chart, Dataset, GraphType, Scrollbar, ScrollbarOrientation},
     Frame,
 };
 
+use super::{
+    cursor::{self, Cursor},
+    properties::fraction_along,
+};
 use crate::{
-    graphics::{Bound, Bounds},
-    messages::{EventList, Trace},
+    cli_structs::{IntensityScale, UserBounds},
+    graphics::{Bound, Bounds, Point},
+    messages::{AxisLabel, EventList, Pipelines, PointConversion, Trace, UnitConversion},
     tui::{
-        ComponentStyle, GraphProperties, ParentalFocusComponent, TuiComponent, TuiComponentBuilder,
+        ComponentStyle, GraphProperties, InputComponent, ParentalFocusComponent, TuiComponent,
+        TuiComponentBuilder, ZoomAxes,
     },
     Component,
 };
 
+/// A drag of the mouse in progress over the graph.
+enum Drag {
+    /// The view is panned so the data follows the mouse, which was last at the given position.
+    Pan(Position),
+    /// The view will be zoomed to the box between the given points, in the units of the graph.
+    Zoom(Point, Point),
+    /// The view is moved along the time axis with the horizontal scrollbar.
+    HorizontalScroll,
+    /// The view is moved along the intensity axis with the vertical scrollbar.
+    VerticalScroll,
+}
+
+/// A trace and its event list, and how they are graphed, as given to [Graph::set].
+#[derive(Clone)]
+pub(crate) struct GraphData {
+    /// The raw trace.
+    pub(crate) trace: Trace,
+    /// The raw event list of the trace, if present.
+    pub(crate) events: Option<EventList>,
+    /// The bounds to use in place of those fitted to the data, where set.
+    pub(crate) user_bounds: UserBounds,
+    /// The conversion into physical units to apply to the data, if any.
+    pub(crate) conversion: Option<UnitConversion>,
+    /// The pipelines whose results are graphed alongside the trace.
+    pub(crate) processing: Pipelines,
+}
+
 /// Encapsulates and displays the [ratatui] graph of a given trace and eventlist.
 pub(crate) struct Graph {
     /// Flag specifying whether an ancestor object has the focus or not.
@@ -23,12 +59,28 @@ pub(crate) struct Graph {
     trace_data: Vec<(f64, f64)>,
     /// The raw event list of the graph, if present.
     event_data: Option<Vec<(f64, f64)>>,
+    /// The events detected locally in the trace, if a detector is set.
+    detected_data: Option<Vec<(f64, f64)>>,
+    /// The processed traces of the graph, each named after the pipeline which produced it.
+    processed_data: Vec<(String, Vec<(f64, f64)>)>,
     ///
     properties: Option<GraphProperties>,
-    /// The current state of the horizontal scrollbar.
-    hscroll_state: ScrollbarState,
-    /// The current state of the vertical scrollbar.
-    vscroll_state: ScrollbarState,
+    /// The conversion into physical units applied to the data, if any.
+    conversion: Option<UnitConversion>,
+    /// Whether the intensities of the data are graphed on a linear scale.
+    is_linear: bool,
+    /// The cursor whose position is read out, if placed.
+    cursor: Option<Cursor>,
+    /// The cursor from which the differences to [Self::cursor] are read out, if placed.
+    second_cursor: Option<Cursor>,
+    /// The drag of the mouse in progress, if any.
+    drag: Option<Drag>,
+    /// The area the chart was last rendered in.
+    chart_area: Cell<Rect>,
+    /// The area the horizontal scrollbar was last rendered in.
+    hscroll_area: Cell<Rect>,
+    /// The area the vertical scrollbar was last rendered in.
+    vscroll_area: Cell<Rect>,
 }
 
 impl Graph {
@@ -36,6 +88,13 @@ impl Graph {
     const VSCROLL_BAR_WIDTH: u16 = 2;
     /// The height of the horizontal scrollbar.
     const HSCROLL_BAR_HEIGHT: u16 = 2;
+    /// The colours of the processed traces, in the order of their pipelines, repeated if there are more.
+    const PROCESSED_COLOURS: [Color; 4] = [
+        Color::Cyan,
+        Color::Magenta,
+        Color::LightGreen,
+        Color::LightBlue,
+    ];
 
     pub(crate) fn new() -> TuiComponent<Self> {
         TuiComponentBuilder::new(ComponentStyle::selectable())
@@ -43,23 +102,52 @@ impl Graph {
             .build(Self {
                 trace_data: Default::default(),
                 event_data: None,
+                detected_data: None,
+                processed_data: Default::default(),
                 parent_has_focus: false,
                 properties: None,
-                hscroll_state: ScrollbarState::default(),
-                vscroll_state: ScrollbarState::default(),
+                conversion: None,
+                is_linear: true,
+                cursor: None,
+                second_cursor: None,
+                drag: None,
+                chart_area: Cell::default(),
+                hscroll_area: Cell::default(),
+                vscroll_area: Cell::default(),
             })
     }
 
     /// Sets the trace and eventlist data of the graph, and computes the [Self::properties].
     ///
     /// # Attributes
-    /// - trace_data: the trace data to load.
-    /// - event_data: the event list data to load, if available.
-    pub(crate) fn set(&mut self, trace_data: &Trace, event_data: Option<&EventList>) {
-        let trace_data: Vec<_> = (0_u32..).zip(trace_data.iter().copied()).collect();
+    /// - data: the trace and event list to load, and how to graph them.
+    /// - detected_data: the events detected locally in the trace, if a detector is set.
+    /// - scale: the scale of the intensity axis.
+    /// - keep_view: if true, the zoom and viewport of the previous data are kept, otherwise the whole data is in view.
+    pub(crate) fn set(
+        &mut self,
+        data: &GraphData,
+        detected_data: Option<&EventList>,
+        scale: &IntensityScale,
+        keep_view: bool,
+    ) {
+        let GraphData {
+            trace,
+            events: event_data,
+            user_bounds,
+            conversion,
+            processing,
+        } = data;
+        let conversion = *conversion;
+        let point_conversion = PointConversion::new(
+            conversion.as_ref(),
+            scale.transform(trace, conversion.as_ref()),
+        );
+        let trace_data: Vec<_> = (0_u32..).zip(trace.iter().copied()).collect();
 
-        let event_data: Option<Vec<_>> =
-            event_data.map(|events| events.iter().map(|e| (e.time, e.intensity)).collect());
+        let event_data: Option<Vec<_>> = event_data
+            .as_ref()
+            .map(|events| events.iter().map(|e| (e.time, e.intensity)).collect());
 
         let time = trace_data.iter().map(|e| e.0 as u32);
         let time_bounds = Bound::from(1.0625, time.clone());
@@ -67,28 +155,84 @@ impl Graph {
         let values = trace_data.iter().map(|e| e.1);
         let intensity_bounds = Bound::from(1.125, values.clone());
 
-        let properties = GraphProperties::new(Bounds {
+        let mut bounds = Bounds {
             time: time_bounds,
             intensity: intensity_bounds,
-        });
+        }
+        .include_processed(&[trace], processing, conversion.as_ref())
+        .amend_with_user_bounds(user_bounds);
+        let num_samples = bounds.time.range();
+        if let Some(conversion) = &conversion {
+            bounds = bounds.convert(conversion);
+        }
+        let labels = AxisLabel::for_graph(conversion.as_ref(), bounds.time.range(), scale);
+
+        let convert = |(t, v): (f64, f64)| point_conversion.apply(t, v);
 
         self.trace_data = trace_data
             .iter()
             .copied()
-            .map(|(t, v)| (t as f64, v as f64))
+            .map(|(t, v)| convert((t as f64, v as f64)))
             .collect();
 
         self.event_data = event_data.as_ref().map(|event_data| {
             event_data
                 .iter()
                 .copied()
-                .map(|e| (e.0 as f64, e.1 as f64))
+                .map(|e| convert((e.0 as f64, e.1 as f64)))
                 .collect::<Vec<_>>()
         });
+        self.detected_data = detected_data.map(|events| {
+            events
+                .iter()
+                .map(|e| convert((e.time as f64, e.intensity as f64)))
+                .collect()
+        });
+
+        self.processed_data = processing
+            .pipelines()
+            .iter()
+            .map(|pipeline| {
+                let point_conversion = point_conversion.for_pipeline(pipeline);
+                let data = pipeline
+                    .apply(trace)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(t, v)| point_conversion.apply(t as f64, v))
+                    .collect();
+                (pipeline.to_string(), data)
+            })
+            .collect();
+        self.conversion = conversion;
+        self.is_linear = scale.is_linear();
+
+        // The bounds of the raw intensities do not apply to rescaled intensities.
+        if !self.is_linear {
+            let intensities = self
+                .trace_data
+                .iter()
+                .chain(self.event_data.iter().flatten())
+                .chain(self.processed_data.iter().flat_map(|(_, data)| data))
+                .filter(|(t, _)| bounds.time.min <= *t && *t <= bounds.time.max)
+                .map(|(_, intensity)| *intensity);
+            if let Some(intensity) = Bound::fit(Bound::FIT_MARGIN, intensities) {
+                bounds.intensity = intensity;
+            }
+        }
+        let mut properties = GraphProperties::new(bounds, labels, num_samples);
+        if let Some(previous) = self.properties.as_ref().filter(|_| keep_view) {
+            properties.keep_view_of(previous);
+        }
+
+        // Cursors are kept between traces, so long as the point they are snapped to still exists.
+        self.cursor = self
+            .cursor
+            .filter(|&cursor| self.cursor_point(cursor).is_some());
+        self.second_cursor = self
+            .second_cursor
+            .filter(|&cursor| self.cursor_point(cursor).is_some());
 
         self.properties = Some(properties);
-        self.hscroll_state = ScrollbarState::new(100).viewport_content_length(100);
-        self.vscroll_state = ScrollbarState::new(100).viewport_content_length(100);
     }
 
     /// Grants mutable access to the graph's properties object.
@@ -96,6 +240,172 @@ impl Graph {
         self.properties.as_mut()
     }
 
+    /// Returns the bounding rectangle of the data currently in view, in sample indices and raw intensities.
+    ///
+    /// If the intensities are not on a linear scale, the intensity bounds have no meaning.
+    pub(crate) fn get_zoomed_bounds(&self) -> Option<Bounds> {
+        self.properties.as_ref().map(|properties| {
+            let bounds = properties.get_zoomed_bounds();
+            match &self.conversion {
+                Some(conversion) => bounds.unconvert(conversion),
+                None => bounds.clone(),
+            }
+        })
+    }
+
+    /// Zooms the intensity axis to fit the trace samples, events and processed traces in view, with a margin of [Bound::FIT_MARGIN].
+    pub(crate) fn zoom_to_fit(&mut self) {
+        let Some(properties) = self.properties.as_mut() else {
+            return;
+        };
+        let time = properties.zoomed_bounds.time.clone();
+        let intensities = self
+            .trace_data
+            .iter()
+            .chain(self.event_data.iter().flatten())
+            .chain(self.processed_data.iter().flat_map(|(_, data)| data))
+            .filter(|(t, _)| time.min <= *t && *t <= time.max)
+            .map(|(_, intensity)| *intensity);
+        if let Some(intensity) = Bound::fit(Bound::FIT_MARGIN, intensities) {
+            properties.zoom_to(&Bounds { time, intensity });
+        }
+    }
+
+    /// Zooms to the ranges given in sample indices and raw intensities, where set,
+    /// leaving the view of any other axes unchanged.
+    ///
+    /// Intensity ranges are only applied on a linear scale.
+    pub(crate) fn zoom_to_user_bounds(&mut self, user_bounds: &UserBounds) {
+        let Some(bounds) = self.get_zoomed_bounds() else {
+            return;
+        };
+        let user_bounds = if self.is_linear {
+            user_bounds.clone()
+        } else {
+            UserBounds {
+                intensity_min: None,
+                intensity_max: None,
+                ..user_bounds.clone()
+            }
+        };
+        let mut bounds = bounds.amend_with_user_bounds(&user_bounds);
+        if let Some(conversion) = &self.conversion {
+            bounds = bounds.convert(conversion);
+        }
+        if bounds.time.range() > 0.0 && bounds.intensity.range() > 0.0 {
+            if let Some(properties) = self.properties.as_mut() {
+                properties.zoom_to(&bounds);
+            }
+        }
+    }
+
+    /// Returns the point of the data the cursor is snapped to, if it still exists.
+    fn cursor_point(&self, cursor: Cursor) -> Option<(f64, f64)> {
+        cursor.point(&self.trace_data, self.event_data.as_deref())
+    }
+
+    /// Returns the sample index of the cursor, which for an event is its time in samples.
+    fn cursor_sample(&self, cursor: Cursor) -> Option<usize> {
+        match cursor {
+            Cursor::Sample(index) => Some(index),
+            Cursor::Event(_) => self.cursor_point(cursor).map(|(time, _)| {
+                let time = match &self.conversion {
+                    Some(conversion) => conversion.raw_time(time),
+                    None => time,
+                };
+                time.round() as usize
+            }),
+        }
+    }
+
+    /// Moves the cursor by the given number of trace samples.
+    ///
+    /// If the cursor is on an event, it is first snapped to the nearest sample,
+    /// and if there is no cursor, it is placed on the sample nearest the centre of the view.
+    ///
+    /// # Attributes
+    /// - steps: the number of samples to move by, negative values move backwards in time.
+    pub(crate) fn step_cursor(&mut self, steps: isize) {
+        if self.trace_data.is_empty() {
+            return;
+        }
+        let index = match self.cursor {
+            Some(Cursor::Sample(index)) => Some(
+                index
+                    .saturating_add_signed(steps)
+                    .min(self.trace_data.len() - 1),
+            ),
+            Some(cursor) => self
+                .cursor_point(cursor)
+                .and_then(|(time, _)| cursor::nearest_sample(&self.trace_data, time)),
+            None => self.properties.as_ref().and_then(|properties| {
+                cursor::nearest_sample(&self.trace_data, properties.zoomed_bounds.mid_point().time)
+            }),
+        };
+        self.cursor = index.map(Cursor::Sample);
+    }
+
+    /// Moves the cursor to the next event in time, or the previous one if `forwards` is false.
+    ///
+    /// If there is no cursor, it is placed on the first (or last) event in view.
+    pub(crate) fn step_cursor_to_event(&mut self, forwards: bool) {
+        let Some(properties) = &self.properties else {
+            return;
+        };
+        let Some(event_data) = &self.event_data else {
+            return;
+        };
+        let time = match self.cursor.and_then(|cursor| self.cursor_point(cursor)) {
+            Some((time, _)) => time,
+            None if forwards => properties.zoomed_bounds.time.min,
+            None => properties.zoomed_bounds.time.max,
+        };
+        if let Some(index) = cursor::adjacent_event(event_data, time, forwards) {
+            self.cursor = Some(Cursor::Event(index));
+        }
+    }
+
+    /// Places the second cursor at the cursor, or removes it if already placed.
+    pub(crate) fn toggle_second_cursor(&mut self) {
+        self.second_cursor = match self.second_cursor {
+            Some(_) => None,
+            None => self.cursor,
+        };
+    }
+
+    /// Removes both cursors.
+    pub(crate) fn clear_cursors(&mut self) {
+        self.cursor = None;
+        self.second_cursor = None;
+    }
+
+    /// Returns the time, intensity and sample index of the cursor,
+    /// and the differences from the second cursor if it is placed.
+    pub(crate) fn get_cursor_info(&self) -> Option<String> {
+        let properties = self.properties.as_ref()?;
+        let (time_label, intensity_label) = properties.get_labels();
+
+        let cursor = self.cursor?;
+        let (time, intensity) = self.cursor_point(cursor)?;
+        let mut info = format!(
+            "t: {}, I: {}, sample: {}",
+            time_label.readout(time),
+            intensity_label.readout(intensity),
+            self.cursor_sample(cursor)?
+        );
+        if let Some((second_time, second_intensity)) = self
+            .second_cursor
+            .and_then(|second_cursor| self.cursor_point(second_cursor))
+        {
+            info.push_str(&format!(
+                " | Δt: {}, ΔI: {}",
+                time_label.readout_difference(time, second_time),
+                intensity_label.readout_difference(intensity, second_intensity)
+            ));
+        }
+        Some(info)
+    }
+
     /// Grants mutable access to the graph's properties object.
     pub(crate) fn get_properties(&self) -> Option<&GraphProperties> {
         self.properties.as_ref()
@@ -146,10 +456,15 @@ impl Component for Graph {
                 (chunk1[0], chunk2[0])
             };
 
+            self.chart_area.set(graph);
+            self.hscroll_area.set(hscroll);
+            self.vscroll_area.set(vscroll);
+
             let horiz_scroll = Scrollbar::new(ScrollbarOrientation::HorizontalBottom);
             let vert_scroll = Scrollbar::new(ScrollbarOrientation::VerticalRight);
-            frame.render_stateful_widget(horiz_scroll, hscroll, &mut self.hscroll_state.clone());
-            frame.render_stateful_widget(vert_scroll, vscroll, &mut self.vscroll_state.clone());
+            let (mut hscroll_state, mut vscroll_state) = properties.scrollbar_states();
+            frame.render_stateful_widget(horiz_scroll, hscroll, &mut hscroll_state);
+            frame.render_stateful_widget(vert_scroll, vscroll, &mut vscroll_state);
 
             let trace_data = self
                 .trace_data
@@ -188,12 +503,107 @@ impl Component for Graph {
                     .data(event_data.as_slice())
             });
 
-            let datasets = if let Some(event_dataset) = event_dataset {
+            let detected_data = self.detected_data.as_ref().map(|detected_data| {
+                detected_data
+                    .iter()
+                    .copied()
+                    .filter(|(time, _)| {
+                        properties.zoomed_bounds.time.min <= *time
+                            && *time <= properties.zoomed_bounds.time.max
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            let processed_data = self
+                .processed_data
+                .iter()
+                .map(|(name, data)| {
+                    let data = data
+                        .iter()
+                        .copied()
+                        .filter(|(time, _)| {
+                            properties.zoomed_bounds.time.min <= *time
+                                && *time <= properties.zoomed_bounds.time.max
+                        })
+                        .collect::<Vec<_>>();
+                    (name, data)
+                })
+                .collect::<Vec<_>>();
+
+            let mut datasets = if let Some(event_dataset) = event_dataset {
                 vec![trace_dataset, event_dataset]
             } else {
                 vec![trace_dataset]
             };
 
+            if let Some(detected_data) = &detected_data {
+                datasets.push(
+                    Dataset::default()
+                        .name("Detected")
+                        .marker(Marker::Dot)
+                        .graph_type(GraphType::Scatter)
+                        .style(Style::new().fg(Color::LightMagenta).bg(Color::Black))
+                        .data(detected_data.as_slice()),
+                );
+            }
+
+            for ((name, data), colour) in processed_data
+                .iter()
+                .zip(Self::PROCESSED_COLOURS.into_iter().cycle())
+            {
+                datasets.push(
+                    Dataset::default()
+                        .name(name.as_str())
+                        .marker(Marker::Braille)
+                        .graph_type(GraphType::Line)
+                        .style(Style::new().fg(colour).bg(Color::Black))
+                        .data(data.as_slice()),
+                );
+            }
+
+            let crosshairs = [
+                (self.second_cursor, Color::Green),
+                (self.cursor, Color::Yellow),
+            ]
+            .into_iter()
+            .filter_map(|(cursor, colour)| {
+                let point = self.cursor_point(cursor?)?;
+                Some((cursor::crosshair(point, &properties.zoomed_bounds), colour))
+            })
+            .collect::<Vec<_>>();
+
+            let zoom_box = match &self.drag {
+                Some(Drag::Zoom(from, to)) => vec![
+                    (from.time, from.intensity),
+                    (to.time, from.intensity),
+                    (to.time, to.intensity),
+                    (from.time, to.intensity),
+                    (from.time, from.intensity),
+                ],
+                _ => Vec::new(),
+            };
+            if !zoom_box.is_empty() {
+                datasets.push(
+                    Dataset::default()
+                        .marker(Marker::Braille)
+                        .graph_type(GraphType::Line)
+                        .style(Style::new().fg(Color::White).bg(Color::Black))
+                        .data(zoom_box.as_slice()),
+                );
+            }
+
+            for (lines, colour) in &crosshairs {
+                for line in lines {
+                    datasets.push(
+                        Dataset::default()
+                            .marker(Marker::Braille)
+                            .graph_type(GraphType::Line)
+                            .style(Style::new().fg(*colour).bg(Color::Black))
+                            .data(line),
+                    );
+                }
+            }
+
             let chart = Chart::new(datasets)
                 .x_axis(properties.x_axis.clone())
                 .y_axis(properties.y_axis.clone());
@@ -203,6 +613,96 @@ impl Component for Graph {
     }
 }
 
+impl InputComponent for Graph {
+    /// Key presses are handled by the parent component, as they also affect its info.
+    fn handle_key_press(&mut self, _key: KeyEvent) {}
+
+    /// The scroll wheel zooms around the mouse, with control or alt to zoom only the time or intensity axis, a left-drag pans the view or moves a scrollbar,
+    /// and a right-drag zooms to the box dragged out.
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        let Some(properties) = self.properties.as_mut() else {
+            return;
+        };
+        let position = Position::new(mouse.column, mouse.row);
+        let plot_area = properties.plot_area(self.chart_area.get());
+        let hscroll_area = self.hscroll_area.get();
+        let vscroll_area = self.vscroll_area.get();
+        let hscroll_to =
+            |position: Position| fraction_along(position.x, hscroll_area.x, hscroll_area.width);
+        let vscroll_to =
+            |position: Position| fraction_along(position.y, vscroll_area.y, vscroll_area.height);
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+                if plot_area.contains(position) =>
+            {
+                // Holding control zooms only the time axis, and alt only the intensity axis.
+                let axes = if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+                    ZoomAxes::Time
+                } else if mouse.modifiers.contains(KeyModifiers::ALT) {
+                    ZoomAxes::Intensity
+                } else {
+                    ZoomAxes::Both
+                };
+                let point = properties.point_at(plot_area, position);
+                properties.zoom_at(&point, axes, mouse.kind == MouseEventKind::ScrollUp);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if hscroll_area.contains(position) {
+                    properties.scroll_to(Some(hscroll_to(position)), None);
+                    self.drag = Some(Drag::HorizontalScroll);
+                } else if vscroll_area.contains(position) {
+                    properties.scroll_to(None, Some(vscroll_to(position)));
+                    self.drag = Some(Drag::VerticalScroll);
+                } else if plot_area.contains(position) {
+                    self.drag = Some(Drag::Pan(position));
+                }
+            }
+            MouseEventKind::Down(MouseButton::Right) if plot_area.contains(position) => {
+                let point = properties.point_at(plot_area, position);
+                self.drag = Some(Drag::Zoom(point.clone(), point));
+            }
+            MouseEventKind::Drag(_) => match &mut self.drag {
+                Some(Drag::Pan(from)) => {
+                    let from_point = properties.point_at(plot_area, *from);
+                    let to_point = properties.point_at(plot_area, position);
+                    properties.pan(
+                        from_point.time - to_point.time,
+                        from_point.intensity - to_point.intensity,
+                    );
+                    *from = position;
+                }
+                Some(Drag::Zoom(_, to)) => *to = properties.point_at(plot_area, position),
+                Some(Drag::HorizontalScroll) => {
+                    properties.scroll_to(Some(hscroll_to(position)), None)
+                }
+                Some(Drag::VerticalScroll) => {
+                    properties.scroll_to(None, Some(vscroll_to(position)))
+                }
+                None => {}
+            },
+            MouseEventKind::Up(_) => {
+                if let Some(Drag::Zoom(from, to)) = self.drag.take() {
+                    let bounds = Bounds {
+                        time: Bound {
+                            min: from.time.min(to.time),
+                            max: from.time.max(to.time),
+                        },
+                        intensity: Bound {
+                            min: from.intensity.min(to.intensity),
+                            max: from.intensity.max(to.intensity),
+                        },
+                    };
+                    if bounds.time.range() > 0.0 && bounds.intensity.range() > 0.0 {
+                        properties.zoom_to(&bounds);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl ParentalFocusComponent for Graph {
     fn propagate_parental_focus(&mut self, focus: bool) {
         self.parent_has_focus = focus;
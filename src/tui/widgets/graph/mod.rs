@@ -1,5 +1,6 @@
+mod cursor;
 mod graph;
 mod properties;
 
-pub(crate) use graph::Graph;
-pub(crate) use properties::GraphProperties;
+pub(crate) use graph::{Graph, GraphData};
+pub(crate) use properties::{GraphProperties, ZoomAxes};
@@ -1,22 +1,101 @@
-use ratatui::{text::Span, widgets::Axis};
+use ratatui::{
+    layout::{Position, Rect},
+    text::Span,
+    widgets::{Axis, ScrollbarState},
+};
 
-use crate::graphics::{Bound, Bounds, Point};
+use crate::{
+    graphics::{Bound, Bounds, Point},
+    messages::AxisLabel,
+};
 
-/// Generate a [ratatui] axis object.
+/// Generate the labels of a [ratatui] axis.
 ///
 /// # Attributes
 /// - bound: the source bound of the axis.
-/// - title: the title to display.
+/// - label: the title and label format of the axis.
 /// - num_labels: the number of labels to generate.
-fn make_axis<'a>(bound: &Bound, title: &'static str, num_labels: i32) -> Axis<'static> {
-    let labels: Vec<_> = (0..num_labels)
+fn make_labels(bound: &Bound, label: &AxisLabel, num_labels: i32) -> Vec<String> {
+    (0..num_labels)
         .map(|i| bound.range() * i as f64 / num_labels as f64 + bound.min)
-        .map(|v| Span::raw(format!("{v:.3}")))
-        .collect();
+        .map(|v| label.format(v, 3))
+        .collect()
+}
+
+/// Generate a [ratatui] axis object.
+///
+/// # Attributes
+/// - bound: the source bound of the axis.
+/// - label: the title and label format of the axis.
+/// - labels: the labels of the axis, as generated by [make_labels].
+fn make_axis(bound: &Bound, label: &AxisLabel, labels: Vec<String>) -> Axis<'static> {
     Axis::default()
-        .title(title)
+        .title(label.title())
         .bounds([bound.min, bound.max])
-        .labels(labels)
+        .labels(labels.into_iter().map(Span::raw).collect::<Vec<_>>())
+}
+
+/// Returns the number of columns [ratatui] reserves to the left of the y-axis of a chart.
+///
+/// This is the width of the widest y-axis label, or of the overhang of the first x-axis label if that is wider.
+fn label_margin(x_labels: &[String], y_labels: &[String]) -> u16 {
+    let y_width = y_labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default();
+    let x_overhang = x_labels
+        .first()
+        .map(|label| label.chars().count().saturating_sub(1))
+        .unwrap_or_default();
+    y_width.max(x_overhang) as u16
+}
+
+/// The number of positions a scrollbar resolves the whole of an axis into.
+const SCROLLBAR_RESOLUTION: f64 = 1000.0;
+
+/// Returns the state of a scrollbar showing where the view lies along the whole of an axis.
+///
+/// The thumb of a [ratatui] scrollbar spans `position..position + viewport_content_length`
+/// out of `content_length - 1 + viewport_content_length`, so these are chosen to sum to [SCROLLBAR_RESOLUTION].
+///
+/// # Attributes
+/// - start: the distance from the start of the axis to the start of the view.
+/// - view: the length of the view.
+/// - length: the length of the whole axis.
+fn scrollbar_state(start: f64, view: f64, length: f64) -> ScrollbarState {
+    let viewport = (SCROLLBAR_RESOLUTION * view / length).clamp(1.0, SCROLLBAR_RESOLUTION) as usize;
+    let max_position = SCROLLBAR_RESOLUTION as usize - viewport;
+    let position = (SCROLLBAR_RESOLUTION * start / length).clamp(0.0, max_position as f64) as usize;
+    ScrollbarState::new(max_position + 1)
+        .viewport_content_length(viewport)
+        .position(position)
+}
+
+/// Returns how far a coordinate is along a span of cells, from `0` at its first cell to `1` at its last.
+///
+/// Coordinates outside of the span are clamped to its ends.
+pub(super) fn fraction_along(coordinate: u16, start: u16, length: u16) -> f64 {
+    let offset = coordinate.saturating_sub(start);
+    (offset as f64 / length.saturating_sub(1).max(1) as f64).clamp(0.0, 1.0)
+}
+
+/// The axes to which a change of zoom applies.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ZoomAxes {
+    Both,
+    Time,
+    Intensity,
+}
+
+impl ZoomAxes {
+    fn time(self) -> bool {
+        self != ZoomAxes::Intensity
+    }
+
+    fn intensity(self) -> bool {
+        self != ZoomAxes::Time
+    }
 }
 
 /// Encapsulates the properties of a Tui Graph, that are independent of the data.
@@ -29,66 +108,108 @@ pub(crate) struct GraphProperties {
     ///
     /// This, along with [Self::zoom_factor] is applied to [Self::bounds] to compute [Self::zoomed_bounds].
     pub(super) view_port: Point,
-    /// The scaling factor to apply to the data.
+    /// The scaling factors to apply to each axis of the data.
     ///
     /// This, along with [Self::view_port] is applied to [Self::bounds] to compute [Self::zoomed_bounds].
-    pub(super) zoom_factor: f64,
+    pub(super) zoom_factor: Point,
+    /// The scaling factors are capped above by these values.
+    max_zoom: Point,
     /// The horizontal (time) axis of the graph.
     pub(super) x_axis: Axis<'static>,
     /// The vertical (intensity) axis of the graph.
     pub(super) y_axis: Axis<'static>,
+    /// The title and label format of the time axis.
+    time_label: AxisLabel,
+    /// The title and label format of the intensity axis.
+    intensity_label: AxisLabel,
+    /// The number of columns to the left of the y-axis, taken up by the axis labels.
+    label_margin: u16,
 }
 
 impl GraphProperties {
     /// [Self::move_viewport] scales the direction by this multiple of the entire tranformed bounding rectangle [Self::zoomed_bound].
     const SHIFT_COEF: f64 = 0.1;
 
-    /// The zoom factor is capped above by this value.
+    /// The intensity zoom factor, and the time zoom factor of short traces, are capped above by this value.
     const MAX_ZOOM: f64 = 64.0;
+    /// The time zoom factor of long traces is capped so that no fewer than this many samples are in view.
+    const MIN_SAMPLES_IN_VIEW: f64 = 8.0;
     /// [Self::zoom_in] and [Self::zoom_out] multiply and divide [Self::zoom_factor] by this value, respectively.
     const ZOOM_COEF: f64 = 1.1;
 
-    /// Creates a new instance with the given bounding rectangle, and identity transformation.
-    pub(super) fn new(bounds: Bounds) -> Self {
-        let zoomed_bounds = bounds.clone();
+    /// Creates a new instance with the given bounding rectangle and axis labels, and identity transformation.
+    ///
+    /// # Attributes
+    /// - bounds: the bounding rectangle of the data, in the units of the graph.
+    /// - labels: the title and label format of the time and intensity axes.
+    /// - num_samples: the number of trace samples spanned by the time axis of `bounds`.
+    pub(super) fn new(
+        bounds: Bounds,
+        (time_label, intensity_label): (AxisLabel, AxisLabel),
+        num_samples: f64,
+    ) -> Self {
         let view_port = bounds.mid_point();
-
-        let x_axis = make_axis(&bounds.time, "Time", 10);
-        let y_axis = make_axis(&bounds.intensity, "Intensity", 5);
-        Self {
+        let mut properties = Self {
+            zoomed_bounds: bounds.clone(),
             bounds,
-            zoomed_bounds,
             view_port,
-            zoom_factor: 1.0,
-            x_axis,
-            y_axis,
-        }
+            zoom_factor: Point {
+                time: 1.0,
+                intensity: 1.0,
+            },
+            max_zoom: Point {
+                time: (num_samples / Self::MIN_SAMPLES_IN_VIEW).max(Self::MAX_ZOOM),
+                intensity: Self::MAX_ZOOM,
+            },
+            x_axis: Axis::default(),
+            y_axis: Axis::default(),
+            time_label,
+            intensity_label,
+            label_margin: 0,
+        };
+        properties.calc_axes();
+        properties
+    }
+
+    /// Takes the scaling factors and viewport of the other instance, so that the view is kept when the data is replaced.
+    ///
+    /// The scaling factors are capped by those of this instance.
+    pub(super) fn keep_view_of(&mut self, other: &GraphProperties) {
+        self.zoom_factor = Point {
+            time: other.zoom_factor.time.clamp(1.0, self.max_zoom.time),
+            intensity: other
+                .zoom_factor
+                .intensity
+                .clamp(1.0, self.max_zoom.intensity),
+        };
+        self.view_port = other.view_port.clone();
+        self.calc_axes();
     }
 
     /// Calculate the transformed bounding rectangle and rebuild the axes.
     fn calc_axes(&mut self) {
-        self.zoomed_bounds = self.bounds.transform(self.zoom_factor, &self.view_port);
+        self.zoomed_bounds = self.bounds.transform(&self.zoom_factor, &self.view_port);
+
+        let x_labels = make_labels(&self.zoomed_bounds.time, &self.time_label, 10);
+        let y_labels = make_labels(&self.zoomed_bounds.intensity, &self.intensity_label, 5);
+        self.label_margin = label_margin(&x_labels, &y_labels);
 
-        self.x_axis = make_axis(&self.zoomed_bounds.time, "Time", 10);
-        self.y_axis = make_axis(&self.zoomed_bounds.intensity, "Intensity", 5);
+        self.x_axis = make_axis(&self.zoomed_bounds.time, &self.time_label, x_labels);
+        self.y_axis = make_axis(
+            &self.zoomed_bounds.intensity,
+            &self.intensity_label,
+            y_labels,
+        );
     }
 
-    /// Increase the scaling factor.
-    pub(crate) fn zoom_in(&mut self) {
-        self.zoom_factor *= Self::ZOOM_COEF;
-        if self.zoom_factor > Self::MAX_ZOOM {
-            self.zoom_factor = Self::MAX_ZOOM;
-        }
-        self.calc_axes();
+    /// Increase the scaling factor of the given axes.
+    pub(crate) fn zoom_in(&mut self, axes: ZoomAxes) {
+        self.zoom_at(&self.view_port.clone(), axes, true);
     }
 
-    /// Decrease the scaling factor.
-    pub(crate) fn zoom_out(&mut self) {
-        self.zoom_factor /= Self::ZOOM_COEF;
-        if self.zoom_factor < 1.0 {
-            self.zoom_factor = 1.0;
-        }
-        self.calc_axes();
+    /// Decrease the scaling factor of the given axes.
+    pub(crate) fn zoom_out(&mut self, axes: ZoomAxes) {
+        self.zoom_at(&self.view_port.clone(), axes, false);
     }
 
     /// Translate the viewport in the given direction.
@@ -106,11 +227,153 @@ impl GraphProperties {
         self.calc_axes();
     }
 
-    /// Returns a string with viewport and zoom factor.
+    /// Multiplies or divides the scaling factor of the given axes, keeping the given point at the same position in the view.
+    ///
+    /// # Attributes
+    /// - point: the point to zoom around, in the units of the graph.
+    /// - axes: the axes whose scaling factors should change.
+    /// - zoom_in: if true the scaling factors are increased, otherwise they are decreased.
+    pub(crate) fn zoom_at(&mut self, point: &Point, axes: ZoomAxes, zoom_in: bool) {
+        let coef = if zoom_in {
+            Self::ZOOM_COEF
+        } else {
+            1.0 / Self::ZOOM_COEF
+        };
+        if axes.time() {
+            let old_zoom_factor = self.zoom_factor.time;
+            self.zoom_factor.time = (old_zoom_factor * coef).clamp(1.0, self.max_zoom.time);
+            self.view_port.time = point.time
+                + (self.view_port.time - point.time) * old_zoom_factor / self.zoom_factor.time;
+        }
+        if axes.intensity() {
+            let old_zoom_factor = self.zoom_factor.intensity;
+            self.zoom_factor.intensity =
+                (old_zoom_factor * coef).clamp(1.0, self.max_zoom.intensity);
+            self.view_port.intensity = point.intensity
+                + (self.view_port.intensity - point.intensity) * old_zoom_factor
+                    / self.zoom_factor.intensity;
+        }
+        self.calc_axes();
+    }
+
+    /// Translate the viewport by the given amount, in the units of the graph.
+    pub(crate) fn pan(&mut self, time: f64, intensity: f64) {
+        self.view_port.time += time;
+        self.view_port.intensity += intensity;
+        self.calc_axes();
+    }
+
+    /// Centres the viewport on the given rectangle, and zooms each axis so that it fills the view.
+    ///
+    /// As the scaling factors are capped, the view may be larger than the rectangle.
+    pub(crate) fn zoom_to(&mut self, bounds: &Bounds) {
+        self.view_port = bounds.mid_point();
+        self.zoom_factor = Point {
+            time: (self.bounds.time.range() / bounds.time.range()).clamp(1.0, self.max_zoom.time),
+            intensity: (self.bounds.intensity.range() / bounds.intensity.range())
+                .clamp(1.0, self.max_zoom.intensity),
+        };
+        self.calc_axes();
+    }
+
+    /// Moves the viewport to the given positions along the whole of each axis.
+    ///
+    /// # Attributes
+    /// - time: the position of the viewport along the time axis, from `0` at the minimum to `1` at the maximum, if it should move.
+    /// - intensity: the position of the viewport along the intensity axis, from `0` at the maximum to `1` at the minimum, if it should move.
+    pub(crate) fn scroll_to(&mut self, time: Option<f64>, intensity: Option<f64>) {
+        if let Some(time) = time {
+            self.view_port.time = self.bounds.time.min + time * self.bounds.time.range();
+        }
+        if let Some(intensity) = intensity {
+            self.view_port.intensity =
+                self.bounds.intensity.max - intensity * self.bounds.intensity.range();
+        }
+        self.calc_axes();
+    }
+
+    /// Translates the viewport along the time axis by the given number of views, without passing either end of the axis.
+    pub(crate) fn page(&mut self, pages: f64) {
+        let half_view = self.zoomed_bounds.time.range() / 2.0;
+        self.view_port.time = (self.view_port.time + pages * self.zoomed_bounds.time.range())
+            .min(self.bounds.time.max - half_view)
+            .max(self.bounds.time.min + half_view);
+        self.calc_axes();
+    }
+
+    /// Moves the viewport to the start of the time axis.
+    pub(crate) fn scroll_to_start(&mut self) {
+        self.view_port.time = self.bounds.time.min + self.zoomed_bounds.time.range() / 2.0;
+        self.calc_axes();
+    }
+
+    /// Moves the viewport to the end of the time axis.
+    pub(crate) fn scroll_to_end(&mut self) {
+        self.view_port.time = self.bounds.time.max - self.zoomed_bounds.time.range() / 2.0;
+        self.calc_axes();
+    }
+
+    /// Returns the states of the horizontal and vertical scrollbars, showing where the view lies within the whole graph.
+    pub(crate) fn scrollbar_states(&self) -> (ScrollbarState, ScrollbarState) {
+        let horizontal = scrollbar_state(
+            self.zoomed_bounds.time.min - self.bounds.time.min,
+            self.zoomed_bounds.time.range(),
+            self.bounds.time.range(),
+        );
+        // The vertical scrollbar starts at the top, which is the maximum intensity.
+        let vertical = scrollbar_state(
+            self.bounds.intensity.max - self.zoomed_bounds.intensity.max,
+            self.zoomed_bounds.intensity.range(),
+            self.bounds.intensity.range(),
+        );
+        (horizontal, vertical)
+    }
+
+    /// Returns the area in which data is plotted, given the area of the whole chart.
+    ///
+    /// This mirrors the layout of [ratatui::widgets::Chart], which reserves the columns left of the y-axis,
+    /// and the two rows below the plot for the x-axis and its labels.
+    pub(crate) fn plot_area(&self, chart_area: Rect) -> Rect {
+        let margin = self.label_margin.min(chart_area.width / 3) + 1;
+        Rect::new(
+            chart_area.x + margin,
+            chart_area.y,
+            chart_area.width.saturating_sub(margin),
+            chart_area.height.saturating_sub(2),
+        )
+    }
+
+    /// Returns the point of the data in view at the given position of the plot area.
+    ///
+    /// Positions outside of the plot area are clamped to its edges.
+    pub(crate) fn point_at(&self, plot_area: Rect, position: Position) -> Point {
+        let time = fraction_along(position.x, plot_area.x, plot_area.width);
+        let intensity = fraction_along(position.y, plot_area.y, plot_area.height);
+        Point {
+            time: self.zoomed_bounds.time.min + time * self.zoomed_bounds.time.range(),
+            intensity: self.zoomed_bounds.intensity.max
+                - intensity * self.zoomed_bounds.intensity.range(),
+        }
+    }
+
+    /// Returns the bounding rectangle of the data currently in view.
+    pub(crate) fn get_zoomed_bounds(&self) -> &Bounds {
+        &self.zoomed_bounds
+    }
+
+    /// Returns the title and label format of the time and intensity axes.
+    pub(crate) fn get_labels(&self) -> (&AxisLabel, &AxisLabel) {
+        (&self.time_label, &self.intensity_label)
+    }
+
+    /// Returns a string with viewport and zoom factors.
     pub(crate) fn get_info(&self) -> String {
         format!(
-            "({:.2}, {:.2}): {:.2}",
-            self.view_port.time, self.view_port.intensity, self.zoom_factor,
+            "({:.2}, {:.2}): ({:.2}, {:.2})",
+            self.view_port.time,
+            self.view_port.intensity,
+            self.zoom_factor.time,
+            self.zoom_factor.intensity,
         )
     }
 }
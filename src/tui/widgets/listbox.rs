@@ -1,8 +1,8 @@
-use std::{io::Stdout, str::FromStr};
+use std::{cell::Cell, io::Stdout, str::FromStr};
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     prelude::CrosstermBackend,
     style::{Color, Style},
     symbols,
@@ -27,6 +27,10 @@ pub(crate) struct ListBox<D> {
     parent_has_focus: bool,
     data: Vec<D>,
     state: ListState,
+    /// The area the list was last rendered in, used to find the entry under the mouse.
+    list_area: Cell<Rect>,
+    /// The index of the first entry shown when the list was last rendered.
+    rendered_offset: Cell<usize>,
 }
 
 impl<D> ListBox<D>
@@ -52,6 +56,8 @@ where
             parent_has_focus: false,
             state: ListState::default().with_selected(index),
             has_state_changed: true,
+            list_area: Cell::default(),
+            rendered_offset: Cell::default(),
         })
     }
 
@@ -77,6 +83,11 @@ where
             .and_then(|i| self.data.iter().skip(i).next().copied().clone())
     }
 
+    /// Selects the entry at the given index, without flagging a change of state.
+    pub(crate) fn set_index(&mut self, index: Option<usize>) {
+        self.state.select(index);
+    }
+
     pub(crate) fn get_index(&self) -> Option<usize> {
         if self.data.is_empty() {
             None
@@ -85,6 +96,39 @@ where
         }
     }
 
+    /// Selects the next entry, or the previous one if `forwards` is false, wrapping around at either end.
+    fn step_selection(&mut self, forwards: bool) {
+        let len = self.data.len();
+        let index = match self.state.selected() {
+            Some(selection) if forwards => (selection + 1) % len,
+            Some(selection) => (len + selection - 1) % len,
+            None => 0,
+        };
+        self.state.select(Some(index));
+        self.has_state_changed = true;
+    }
+
+    /// Returns the index of the entry shown at the given position, if there is one.
+    fn index_at(&self, position: Position) -> Option<usize> {
+        let list_area = self.list_area.get();
+        if !list_area.contains(position) {
+            return None;
+        }
+        let mut row = list_area.y;
+        for (index, datum) in self
+            .data
+            .iter()
+            .enumerate()
+            .skip(self.rendered_offset.get())
+        {
+            row += ListItem::new(datum.to_string()).height() as u16;
+            if position.y < row {
+                return Some(index);
+            }
+        }
+        None
+    }
+
     pub(crate) fn pop_state_change(&mut self) -> bool {
         let old_state_change = self.has_state_changed;
         if self.has_state_changed {
@@ -122,7 +166,10 @@ where
         .highlight_symbol(symbols::bar::THREE_EIGHTHS)
         .highlight_style(select_style);
 
-        frame.render_stateful_widget(list, list_area, &mut self.state.clone());
+        let mut state = self.state.clone();
+        frame.render_stateful_widget(list, list_area, &mut state);
+        self.list_area.set(list_area);
+        self.rendered_offset.set(state.offset());
 
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
         let mut scrollbar_state = ScrollbarState::default().content_length(18);
@@ -142,21 +189,27 @@ where
         }
         if self.has_focus {
             if key.code == KeyCode::Up {
-                if let Some(selection) = self.state.selected() {
-                    self.state
-                        .select(Some((self.data.len() + selection - 1) % self.data.len()));
-                } else {
-                    self.state.select(Some(0));
-                }
-                self.has_state_changed = true;
+                self.step_selection(false);
             } else if key.code == KeyCode::Down {
-                if let Some(selection) = self.state.selected() {
-                    self.state.select(Some((selection + 1) % self.data.len()));
-                } else {
-                    self.state.select(Some(0));
+                self.step_selection(true);
+            }
+        }
+    }
+
+    fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        if self.data.is_empty() {
+            return;
+        }
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(index) = self.index_at(Position::new(mouse.column, mouse.row)) {
+                    self.state.select(Some(index));
+                    self.has_state_changed = true;
                 }
-                self.has_state_changed = true;
             }
+            MouseEventKind::ScrollUp => self.step_selection(false),
+            MouseEventKind::ScrollDown => self.step_selection(true),
+            _ => {}
         }
     }
 }
@@ -7,7 +7,7 @@ mod textbox;
 
 pub(crate) use channels::Channels;
 pub(crate) use editbox::EditBox;
-pub(crate) use graph::{Graph, GraphProperties};
+pub(crate) use graph::{Graph, GraphData, GraphProperties, ZoomAxes};
 pub(crate) use listbox::ListBox;
 pub(crate) use statusbar::Statusbar;
 pub(crate) use textbox::TextBox;
@@ -31,6 +31,12 @@ enum StatusMessage {
     EventListSearchFinished,
     #[strum(to_string = "Search Halted. Press <Enter> to search again.")]
     SearchHalted,
+    #[strum(to_string = "Search Timed Out after {0} s. Press <Enter> to search again.")]
+    SearchTimedOut(u64),
+    #[strum(
+        to_string = "Search Ended after Scanning {0} Messages. Press <Enter> to search again."
+    )]
+    ScanLimitReached(usize),
     #[strum(to_string = "Search Complete. Press <Enter> to search again.")]
     SearchFinished,
     #[strum(to_string = "{0}")]
@@ -76,6 +82,10 @@ impl Statusbar {
                 self.progress_steps = prog + self.num_step_passes + 2;
             }
             SearchStatus::Halted => self.status.set(StatusMessage::SearchHalted),
+            SearchStatus::TimedOut(secs) => self.status.set(StatusMessage::SearchTimedOut(secs)),
+            SearchStatus::ScanLimitReached(number) => {
+                self.status.set(StatusMessage::ScanLimitReached(number))
+            }
             SearchStatus::Successful => {
                 self.status.set(StatusMessage::SearchFinished);
                 self.progress_steps = 2 * self.num_step_passes + 3;